@@ -0,0 +1,203 @@
+use std::io::{Read, BufReader, BufRead, Error, ErrorKind};
+use std::cmp::Ordering;
+use regex::Regex;
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod merge;
+pub mod shifts;
+pub mod schedule;
+pub mod timeline;
+pub mod window;
+
+pub fn read<R: Read>(io: R) -> Vec<String> {
+    let br = BufReader::new(io);
+    br.lines().map(|l| l.unwrap()).collect()
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Timestamp {
+    pub year : usize,
+    pub month : usize,
+    pub day : usize,
+    pub hour : usize,
+    pub minute : usize,
+}
+
+impl Timestamp {
+    pub fn new(year : usize, month: usize, day: usize, hour: usize, minute: usize) -> Self {
+        Self { year, month, day, hour, minute }
+    }
+
+    fn days_before_year(year: usize) -> usize {
+        // Multiples of 4, 100 and 400 in [0, year)
+        year * 365 + year.div_ceil(4) - year.div_ceil(100) + year.div_ceil(400)
+    }
+
+    fn days_in_month(year: usize, month: usize) -> usize {
+        match month {
+            2 if (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        (1..=12).contains(&self.month) && self.day >= 1 && self.day <= Self::days_in_month(self.year, self.month)
+            && self.hour < 24 && self.minute < 60
+    }
+
+    // Minutes since 0000-01-01 00:00, so differences across days, months and years come out right
+    pub fn to_minutes(&self) -> usize {
+        let mut days = Self::days_before_year(self.year) + self.day - 1;
+        for month in 1..self.month {
+            days += Self::days_in_month(self.year, month);
+        }
+        (days * 24 + self.hour) * 60 + self.minute
+    }
+
+    pub fn from_minutes(minutes: usize) -> Self {
+        let mut days = minutes / (24 * 60);
+        let mut year = days / 366;
+        while Self::days_before_year(year + 1) <= days {
+            year += 1;
+        }
+        days -= Self::days_before_year(year);
+
+        let mut month = 1;
+        while days >= Self::days_in_month(year, month) {
+            days -= Self::days_in_month(year, month);
+            month += 1;
+        }
+
+        Self::new(year, month, days + 1, (minutes / 60) % 24, minutes % 60)
+    }
+
+    pub fn add_minutes(&self, minutes: usize) -> Self {
+        Self::from_minutes(self.to_minutes() + minutes)
+    }
+
+    // Midnight at the start of the following day
+    pub fn next_day(&self) -> Self {
+        if self.day < Self::days_in_month(self.year, self.month) {
+            Self::new(self.year, self.month, self.day + 1, 0, 0)
+        } else if self.month < 12 {
+            Self::new(self.year, self.month + 1, 1, 0, 0)
+        } else {
+            Self::new(self.year + 1, 1, 1, 0, 0)
+        }
+    }
+
+    // The midnight a shift starting at this time covers. Guards sometimes come in before midnight.
+    pub fn night(&self) -> Self {
+        if self.hour >= 12 {
+            self.next_day()
+        } else {
+            Self::new(self.year, self.month, self.day, 0, 0)
+        }
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02} {:02}:{:02}", self.year, self.month, self.day, self.hour, self.minute)
+    }
+}
+
+// Declaration order is also the order events at the same minute are replayed in
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum EventType {
+    ShiftChange (usize),
+    Sleep,
+    Wake,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Event {
+    pub event : EventType,
+    pub time : usize,
+    pub stamp : Timestamp,
+}
+
+impl Event {
+    pub fn from_string(s: &str) -> Result<Self, Error> {
+        // Drastically reduces runtime to not have to compile this thing everytime
+        lazy_static! {
+            static ref event_re: Regex = Regex::new(r"\[(\d+)-(\d+)-(\d+) (\d{2}):(\d{2})\] * (.*)$").unwrap();
+            static ref wake_re: Regex = Regex::new(r"wakes up").unwrap();
+            static ref sleep_re: Regex = Regex::new(r"falls asleep").unwrap();
+            static ref guard_re: Regex = Regex::new(r"Guard #(\d+) begins shift").unwrap();
+        }
+
+        let caps = event_re.captures(s)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Not a valid event"))?;
+
+        let year = caps[1].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let month = caps[2].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let day = caps[3].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let hour = caps[4].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let minute = caps[5].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let stamp = Timestamp::new(year, month, day, hour, minute);
+        if !stamp.is_valid() {
+            return Err(Error::new(ErrorKind::InvalidInput, "Not a valid timestamp"));
+        }
+
+        if wake_re.is_match(&caps[6]) {
+            return Ok(Self {
+                time: stamp.to_minutes(),
+                event: EventType::Wake,
+                stamp,
+            });
+        }
+
+        if sleep_re.is_match(&caps[6]) {
+            return Ok(Self {
+                time: stamp.to_minutes(),
+                event: EventType::Sleep,
+                stamp,
+            });
+        }
+
+        if let Some(guard) = guard_re.captures(&caps[6]) {
+            let guard_id = guard[1].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+            return Ok(Self {
+                time: stamp.to_minutes(),
+                event: EventType::ShiftChange(guard_id),
+                stamp,
+            });
+        }
+
+        Err(Error::new(ErrorKind::InvalidInput, "Unknown Event Type"))
+    }
+}
+
+impl Ord for Event {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.time.cmp(&other.time)
+            .then_with(|| self.event.cmp(&other.event))
+    }
+}
+
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for EventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            EventType::Wake => write!(f, "Wakes up"),
+            EventType::Sleep => write!(f, "Sleepy Bye"),
+            EventType::ShiftChange(id) => write!(f, "Guard {} comes in", id)
+        }
+    }
+}
+
+impl std::fmt::Display for Event {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -- {}", self.stamp, self.event)
+    }
+}
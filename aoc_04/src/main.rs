@@ -1,218 +1,99 @@
-
 use std::fs::File;
-use std::io::{Read, BufReader, BufRead, Error, ErrorKind};
-use std::cmp::Ordering;
-use std::collections::HashMap;
-use regex::Regex;
-
-#[macro_use]
-extern crate lazy_static;
-
-fn read<R: Read>(io: R) -> Vec<String> {
-    let br = BufReader::new(io);
-    br.lines().map(|l| l.unwrap()).collect()
-}
-
-#[derive(Eq, PartialEq)]
-enum EventType {
-    Sleep,
-    Wake,
-    ShiftChange (usize),
-}
-
-#[derive(Eq)]
-struct Event {
-    event : EventType,
-    time : usize,
-    minutes : usize,
-}
-
-
-impl Event {
-    fn convert_time(year : usize, month: usize, day: usize, hour: usize, minute: usize) -> usize {
-        const mins_per_hour: usize = 60;
-        const mins_per_day: usize = mins_per_hour * 24;
-        const mins_per_month: usize = mins_per_day * 31;
-        const mins_per_year: usize = mins_per_month * 12;
-        return year * mins_per_year + month * mins_per_month + day * mins_per_day + hour * mins_per_hour + minute;
-    }
-
-    fn from_string(s: &String) -> Result<Self, Error> {
-        // Drastically reduces runtime to not have to compile this thing everytime
-        lazy_static! {
-            static ref event_re: Regex = Regex::new(r"\[(\d+)-(\d+)-(\d+) (\d{2}):(\d{2})\] * (.*)$").unwrap();
-            static ref wake_re: Regex = Regex::new(r"wakes up").unwrap();
-            static ref sleep_re: Regex = Regex::new(r"falls asleep").unwrap();
-            static ref guard_re: Regex = Regex::new(r"Guard #(\d+) begins shift").unwrap();
-        }
-
-        let caps = event_re.captures(s)
-            .ok_or(Error::new(ErrorKind::InvalidInput, "Not a valid event"))?;
-
-        // println!("Making new claim {}", s);
+use std::io::{Error, ErrorKind};
 
-        let year = caps[1].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-        let month = caps[2].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-        let day = caps[3].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-        let hour = caps[4].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-        let minute = caps[5].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-        let time = Self::convert_time(year, month, day, hour, minute);
+use aoc_04::read;
+use aoc_04::merge;
+use aoc_04::shifts::{self, Mode};
+use aoc_04::schedule::{GuardSchedule, Metric};
+use aoc_04::timeline;
+use aoc_04::window::Window;
 
-        if wake_re.is_match(&caps[6]) {
-            return Ok(Self {
-                time: time,
-                minutes: minute,
-                event: EventType::Wake,
-            });
-        }
+fn main() -> std::io::Result<()> {
+    let args : Vec<String> = std::env::args().skip(1).collect();
+    let mode = if args.iter().any(|arg| arg == "--lenient") {
+        Mode::Lenient
+    } else {
+        Mode::Strict
+    };
 
-        if sleep_re.is_match(&caps[6]) {
-            return Ok(Self {
-                time: time,
-                minutes: minute,
-                event: EventType::Sleep,
-            });
-        }
+    let window : Window = match args.iter().position(|arg| arg == "--window").and_then(|i| args.get(i + 1)) {
+        Some(window) => window.parse()?,
+        None => Window::midnight_hour(),
+    };
 
-        if let Some(guard) = guard_re.captures(&caps[6]) {
-            let guard_id = guard[1].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
-            return Ok(Self {
-                time: time,
-                minutes: minute,
-                event: EventType::ShiftChange(guard_id),
-            });
+    // Everything that isn't a flag or a flag's value is a log file
+    let mut paths : Vec<&str> = vec![];
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--window" | "--csv" => i += 1,
+            arg if arg.starts_with("--") => {},
+            path => paths.push(path),
         }
-
-        Err(Error::new(ErrorKind::InvalidInput, "Unknown Event Type"))
-    }
-}
-
-impl Ord for Event {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.time.cmp(&other.time)
+        i += 1;
     }
-}
-
-impl PartialOrd for Event {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    if paths.is_empty() {
+        paths.push("./input.txt");
     }
-}
 
-impl PartialEq for Event {
-    fn eq(&self, other: &Self) -> bool {
-        self.time == other.time
+    let mut logs : Vec<(String, Vec<String>)> = vec![];
+    for path in paths.iter() {
+        let f = File::open(path)?;
+        logs.push((path.to_string(), read(f)));
     }
-}
 
-impl std::fmt::Display for EventType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match *self {
-            EventType::Wake => write!(f, "Wakes up"),
-            EventType::Sleep => write!(f, "Sleepy Bye"),
-            EventType::ShiftChange(id) => write!(f, "Guard {} comes in", id)
-        }
+    let merged = merge::merge(&logs)?;
+    if merged.duplicates > 0 {
+        println!("Dropped {} duplicate entries", merged.duplicates);
     }
-}
-
-impl std::fmt::Display for Event {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{} -- {}", self.time, self.event)
+    for conflict in merged.conflicts.iter() {
+        println!("Conflict: {}", conflict);
     }
-}
-
-fn main() -> std::io::Result<()> {
-    let f = File::open("./input.txt")?;
-    let mut events : Vec<_> = read(f).iter().map(|s| Event::from_string(s).unwrap()).collect();
-
-    events.sort_unstable();
-    let mut guards : HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
 
-    let mut current_guard : usize = 0;
-    match events[0].event {
-        EventType::ShiftChange(id) => {
-            guards.insert(id, vec![]);
-            current_guard = id;
-        },
-        _ => return Err(Error::new(ErrorKind::InvalidInput, "First event is not a shift change")),
+    let log = match shifts::interpret_with_window(&merged.events, mode, &window) {
+        Ok(log) => log,
+        Err(errors) => {
+            for error in errors.iter() {
+                eprintln!("{}", error);
+            }
+            return Err(Error::new(ErrorKind::InvalidData,
+                                  format!("{} malformed entries in guard log (try --lenient)", errors.len())));
+        }
     };
 
-    let mut fall_asleep : Option<usize> = None;
-
-    for event in events.iter() {
-        println!("Now at {}", event);
-        match event.event {
-            EventType::Wake => {
-                let initial_sleep = fall_asleep.unwrap();
-                let sleepy_time = event.minutes - initial_sleep;
-                guards.get_mut(&current_guard).unwrap().push((initial_sleep, sleepy_time));
-                fall_asleep = None;
-            },
-            EventType::Sleep => {
-                fall_asleep = Some(event.minutes);
-            },
-            EventType::ShiftChange(id) =>  {
-                guards.entry(id).or_insert_with(|| vec!());
-                current_guard = id;
-            },
-        };
+    for repair in log.repairs.iter() {
+        println!("Repaired: {}", repair);
     }
 
-    let mut max_guard = 0;
-    let mut max_num = 0;
-    for (guard, sleeps) in guards.iter() {
-        let mut sum = 0;
-        println!("--------------- {} ------------------", guard);
-        for sleep in sleeps.iter() {
-            sum += sleep.1;
-            println!("{},{}", sleep.0, sleep.1);
-        }
-        println!("");
-        if sum > max_num {
-            max_num = sum;
-            max_guard = *guard;
-        }
+    let nights = timeline::nights(&log.shifts, &window);
+    if args.iter().any(|arg| arg == "--timeline") {
+        print!("{}", timeline::render(&nights, &window));
+        println!();
     }
-
-    println!("sleepy guard is {}", max_guard);
-    let guard = guards.get(&max_guard).unwrap();
-    let mut mins: [usize; 60] = [0;60];
-    for (min, length) in guard.iter() {
-        for i in (*min)..(*min+*length) {
-            mins[i] += 1
-        }
+    if let Some(path) = args.iter().position(|arg| arg == "--csv").and_then(|i| args.get(i + 1)) {
+        let mut out = File::create(path)?;
+        timeline::write_csv(&nights, &mut out)?;
     }
 
-    let mut max = 0;
-    let mut max_val = mins[0];
-    for (index, min) in mins.iter().enumerate() {
-        if *min > max_val {
-            max = index;
-            max_val = *min;
-        }
-    }
+    let schedule = GuardSchedule::with_window(&log.shifts, window);
 
-    println!("sleepy minute is {}", max);
+    println!("guard  shifts  naps  total  average  longest");
+    for stats in schedule.ranked_by(Metric::TotalSleep) {
+        println!("{:>5}  {:>6}  {:>4}  {:>5}  {:>7.1}  {:>7}", stats.guard, stats.shifts, stats.naps,
+                 stats.total_sleep, stats.average_sleep(), stats.longest_nap);
+    }
+    println!();
 
-    let mut max = 0;
-    let mut max_guard : usize = 0;
-    let mut max_minute : usize = 0;
-    let mut min_with_guards: Vec<HashMap<usize, usize>> = vec![HashMap::new(); 60];
-    for (guard, sleeps) in guards.iter() {
-        for (min, length) in sleeps.iter() {
-            for i in (*min)..(*min+*length) {
-                let mut count = min_with_guards[i].entry(*guard).or_insert(0);
-                *count += 1;
-                if *count > max {
-                    max = *count;
-                    max_guard = *guard;
-                    max_minute = i;
-                }
-            }
-        }
+    if let Some(stats) = schedule.top_by(Metric::TotalSleep) {
+        println!("sleepy guard is {}", stats.guard);
+        let (hour, minute) = window.clock(stats.sleepiest_minute().map_or(0, |m| m.0));
+        println!("sleepy minute is {} (at {:02}:{:02})", minute, hour, minute);
     }
 
-    println!("Guard: {}, Min: {}, Count: {}", max_guard, max_minute, max);
+    if let Some((guard, index, count)) = schedule.most_frequent_minute() {
+        let (hour, minute) = window.clock(index);
+        println!("Guard: {}, Min: {} (at {:02}:{:02}), Count: {}", guard, minute, hour, minute, count);
+    }
 
     Ok(())
 }
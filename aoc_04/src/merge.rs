@@ -0,0 +1,145 @@
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use crate::{Event, Timestamp};
+
+// Where an entry came from, line is 1-based
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Source {
+    pub file : String,
+    pub line : usize,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+// Different entries logged for the same minute. They are still replayed in event order
+// (shift change, sleep, wake), but someone should probably look at them.
+#[derive(Debug)]
+pub struct Conflict {
+    pub at : Timestamp,
+    pub entries : Vec<(Event, Source)>,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] has {} different entries:", self.at, self.entries.len())?;
+        for (event, source) in self.entries.iter() {
+            write!(f, "\n    {} ({})", event.event, source)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct Merged {
+    // Sorted and deduplicated
+    pub events : Vec<Event>,
+    pub duplicates : usize,
+    pub conflicts : Vec<Conflict>,
+}
+
+// Combines any number of logs given as (name, lines). Blank lines are skipped, anything else
+// that doesn't parse is an error naming the file and line.
+pub fn merge(logs: &[(String, Vec<String>)]) -> Result<Merged, Error> {
+    let mut entries : Vec<(Event, Source)> = vec![];
+    for (file, lines) in logs.iter() {
+        for (i, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let source = Source { file: file.clone(), line: i + 1 };
+            let event = Event::from_string(line)
+                .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("{}: {}", source, e)))?;
+            entries.push((event, source));
+        }
+    }
+
+    // Stable, so the first source listed for a duplicate is the earliest one given
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut events : Vec<Event> = vec![];
+    let mut duplicates = 0;
+    let mut conflicts : Vec<Conflict> = vec![];
+
+    let mut start = 0;
+    while start < entries.len() {
+        let time = entries[start].0.time;
+        let mut end = start;
+        let mut distinct : Vec<(Event, Source)> = vec![];
+        while end < entries.len() && entries[end].0.time == time {
+            let (event, source) = &entries[end];
+            if distinct.last().is_some_and(|(last, _)| last == event) {
+                duplicates += 1;
+            } else {
+                distinct.push((event.clone(), source.clone()));
+            }
+            end += 1;
+        }
+
+        events.extend(distinct.iter().map(|(event, _)| event.clone()));
+        if distinct.len() > 1 {
+            conflicts.push(Conflict { at: distinct[0].0.stamp, entries: distinct });
+        }
+        start = end;
+    }
+
+    Ok(Merged { events, duplicates, conflicts })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::EventType;
+    use crate::merge::merge;
+
+    fn log(name: &str, lines: &[&str]) -> (String, Vec<String>) {
+        (name.to_string(), lines.iter().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn merges_and_dedups() {
+        let merged = merge(&[
+            log("a", &[
+                "[1518-11-01 00:25] wakes up",
+                "[1518-11-01 00:00] Guard #10 begins shift",
+                "[1518-11-01 00:05] falls asleep",
+            ]),
+            log("b", &[
+                "",
+                "[1518-11-01 00:05] falls asleep",
+                "[1518-11-02 00:00] Guard #99 begins shift",
+            ]),
+        ]).unwrap();
+
+        assert_eq!(merged.events.len(), 4);
+        assert_eq!(merged.duplicates, 1);
+        assert!(merged.conflicts.is_empty());
+        assert_eq!(merged.events[0].event, EventType::ShiftChange(10));
+        assert_eq!(merged.events[3].event, EventType::ShiftChange(99));
+    }
+
+    #[test]
+    fn ties_and_conflicts() {
+        let merged = merge(&[
+            log("a", &[
+                "[1518-11-01 00:05] wakes up",
+                "[1518-11-01 00:05] falls asleep",
+                "[1518-11-01 00:05] Guard #10 begins shift",
+            ]),
+        ]).unwrap();
+
+        let order : Vec<&EventType> = merged.events.iter().map(|e| &e.event).collect();
+        assert_eq!(order, vec![&EventType::ShiftChange(10), &EventType::Sleep, &EventType::Wake]);
+        assert_eq!(merged.conflicts.len(), 1);
+        let lines : Vec<usize> = merged.conflicts[0].entries.iter().map(|(_, s)| s.line).collect();
+        assert_eq!(lines, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn reports_bad_line() {
+        let err = merge(&[log("guards.txt", &["[1518-11-01 00:05] wakes up", "nonsense"])]).unwrap_err();
+        assert!(err.to_string().starts_with("guards.txt:2"));
+    }
+}
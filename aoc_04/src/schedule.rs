@@ -0,0 +1,234 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use crate::shifts::Shift;
+use crate::window::Window;
+
+// Things guards can be ranked by. Rankings are always most-first.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Metric {
+    TotalSleep,
+    Shifts,
+    AverageSleep,
+    LongestNap,
+    // How many times the guard was asleep on their sleepiest minute
+    SleepiestMinute,
+}
+
+#[derive(Debug, Clone)]
+pub struct GuardStats {
+    pub guard : usize,
+    pub shifts : usize,
+    pub naps : usize,
+    pub total_sleep : usize,
+    pub longest_nap : usize,
+    // Number of shifts the guard was asleep for each minute of the window
+    pub minutes : Vec<usize>,
+}
+
+impl GuardStats {
+    fn new(guard: usize, window: &Window) -> Self {
+        Self {
+            guard,
+            shifts: 0,
+            naps: 0,
+            total_sleep: 0,
+            longest_nap: 0,
+            minutes: vec![0; window.length],
+        }
+    }
+
+    pub fn average_sleep(&self) -> f64 {
+        if self.shifts == 0 {
+            return 0.0;
+        }
+        self.total_sleep as f64 / self.shifts as f64
+    }
+
+    // (index into the window, times asleep). Earliest wins a tie, None if the guard never slept.
+    pub fn sleepiest_minute(&self) -> Option<(usize, usize)> {
+        let mut best : Option<(usize, usize)> = None;
+        for (minute, count) in self.minutes.iter().enumerate() {
+            if *count > best.map_or(0, |b| b.1) {
+                best = Some((minute, *count));
+            }
+        }
+        best
+    }
+
+    fn compare(&self, other: &Self, metric: Metric) -> Ordering {
+        match metric {
+            Metric::TotalSleep => self.total_sleep.cmp(&other.total_sleep),
+            Metric::Shifts => self.shifts.cmp(&other.shifts),
+            // Cross multiply so the averages compare exactly
+            Metric::AverageSleep => (self.total_sleep * other.shifts).cmp(&(other.total_sleep * self.shifts)),
+            Metric::LongestNap => self.longest_nap.cmp(&other.longest_nap),
+            Metric::SleepiestMinute => {
+                let count = |s: &Self| s.sleepiest_minute().map_or(0, |m| m.1);
+                count(self).cmp(&count(other))
+            },
+        }
+    }
+}
+
+// Sleep statistics per guard. Only the part of each nap inside the window is counted.
+#[derive(Debug)]
+pub struct GuardSchedule {
+    window : Window,
+    guards : HashMap<usize, GuardStats>,
+}
+
+impl GuardSchedule {
+    pub fn from_shifts(shifts: &[Shift]) -> Self {
+        Self::with_window(shifts, Window::midnight_hour())
+    }
+
+    pub fn with_window(shifts: &[Shift], window: Window) -> Self {
+        let mut guards : HashMap<usize, GuardStats> = HashMap::new();
+
+        for shift in shifts.iter() {
+            let stats = guards.entry(shift.guard).or_insert_with(|| GuardStats::new(shift.guard, &window));
+            stats.shifts += 1;
+            let night = shift.night();
+            for nap in shift.naps.iter() {
+                let range = match window.clip(&night, nap) {
+                    Some(range) => range,
+                    None => continue,
+                };
+                stats.naps += 1;
+                stats.total_sleep += range.len();
+                stats.longest_nap = std::cmp::max(stats.longest_nap, range.len());
+                for count in stats.minutes[range].iter_mut() {
+                    *count += 1;
+                }
+            }
+        }
+
+        Self { window, guards }
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn len(&self) -> usize {
+        self.guards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guards.is_empty()
+    }
+
+    pub fn guard(&self, id: usize) -> Option<&GuardStats> {
+        self.guards.get(&id)
+    }
+
+    pub fn guards(&self) -> impl Iterator<Item = &GuardStats> {
+        self.guards.values()
+    }
+
+    // Highest first, ties broken by lowest guard id so the order is stable
+    pub fn ranked_by(&self, metric: Metric) -> Vec<&GuardStats> {
+        let mut ranked : Vec<&GuardStats> = self.guards.values().collect();
+        ranked.sort_by(|a, b| b.compare(a, metric).then_with(|| a.guard.cmp(&b.guard)));
+        ranked
+    }
+
+    pub fn top_by(&self, metric: Metric) -> Option<&GuardStats> {
+        self.ranked_by(metric).first().cloned()
+    }
+
+    // (guard, index into the window, times asleep) for the guard most frequently asleep on the
+    // same minute
+    pub fn most_frequent_minute(&self) -> Option<(usize, usize, usize)> {
+        let stats = self.top_by(Metric::SleepiestMinute)?;
+        let (minute, count) = stats.sleepiest_minute()?;
+        Some((stats.guard, minute, count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Timestamp;
+    use crate::shifts::{Nap, Shift};
+    use crate::schedule::{GuardSchedule, Metric};
+    use crate::window::Window;
+
+    fn shift(guard: usize, naps: Vec<(usize, usize)>) -> Shift {
+        let began = Timestamp::new(1518, 11, 1, 0, 0);
+        let naps = naps.iter()
+            .map(|(start, length)| Nap { start: began.add_minutes(*start), end: began.add_minutes(start + length) })
+            .collect();
+        Shift { guard, began, naps }
+    }
+
+    // The example from the puzzle description
+    fn example() -> GuardSchedule {
+        GuardSchedule::from_shifts(&[
+            shift(10, vec![(5, 20), (30, 25)]),
+            shift(99, vec![(40, 10)]),
+            shift(10, vec![(24, 5)]),
+            shift(99, vec![(36, 10)]),
+            shift(99, vec![(45, 10)]),
+        ])
+    }
+
+    #[test]
+    fn stats() {
+        let schedule = example();
+        assert_eq!(schedule.len(), 2);
+
+        let ten = schedule.guard(10).unwrap();
+        assert_eq!(ten.shifts, 2);
+        assert_eq!(ten.naps, 3);
+        assert_eq!(ten.total_sleep, 50);
+        assert_eq!(ten.longest_nap, 25);
+        assert_eq!(ten.average_sleep(), 25.0);
+        assert_eq!(ten.sleepiest_minute(), Some((24, 2)));
+
+        let ninety_nine = schedule.guard(99).unwrap();
+        assert_eq!(ninety_nine.total_sleep, 30);
+        assert_eq!(ninety_nine.sleepiest_minute(), Some((45, 3)));
+    }
+
+    #[test]
+    fn strategies() {
+        let schedule = example();
+        assert_eq!(schedule.top_by(Metric::TotalSleep).unwrap().guard, 10);
+        assert_eq!(schedule.most_frequent_minute(), Some((99, 45, 3)));
+    }
+
+    #[test]
+    fn ranking() {
+        let schedule = example();
+        let order = |metric| schedule.ranked_by(metric).iter().map(|s| s.guard).collect::<Vec<_>>();
+        assert_eq!(order(Metric::Shifts), vec![99, 10]);
+        assert_eq!(order(Metric::AverageSleep), vec![10, 99]);
+        assert_eq!(order(Metric::LongestNap), vec![10, 99]);
+        assert_eq!(order(Metric::SleepiestMinute), vec![99, 10]);
+    }
+
+    #[test]
+    fn overnight_window() {
+        let began = Timestamp::new(1518, 11, 1, 23, 45);
+        let shifts = vec![Shift {
+            guard: 10,
+            began,
+            naps: vec![
+                Nap { start: began.add_minutes(5), end: began.add_minutes(30) },
+                Nap { start: began.add_minutes(100), end: began.add_minutes(110) },
+            ],
+        }];
+
+        // The midnight hour only sees 00:00 - 00:15 and 01:25 - 01:35 is outside it
+        let midnight = GuardSchedule::from_shifts(&shifts);
+        let stats = midnight.guard(10).unwrap();
+        assert_eq!((stats.naps, stats.total_sleep, stats.longest_nap), (1, 15, 15));
+
+        let window : Window = "23:00-06:00".parse().unwrap();
+        let overnight = GuardSchedule::with_window(&shifts, window);
+        let stats = overnight.guard(10).unwrap();
+        assert_eq!((stats.naps, stats.total_sleep, stats.longest_nap), (2, 35, 25));
+        let (index, _) = stats.sleepiest_minute().unwrap();
+        assert_eq!(window.clock(index), (23, 50));
+    }
+}
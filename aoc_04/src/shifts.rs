@@ -0,0 +1,261 @@
+use std::fmt;
+use crate::{Event, EventType, Timestamp};
+use crate::window::Window;
+
+// How to treat a log that doesn't follow shift -> (sleep -> wake)* -> shift
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Mode {
+    // Any malformed sequence fails the whole log
+    Strict,
+    // Malformed sequences are repaired and reported back alongside the shifts
+    Lenient,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum LogError {
+    // Repaired by ignoring the wake
+    WakeWithoutSleep { at: Timestamp },
+    // Repaired by keeping the earlier sleep
+    DoubleSleep { asleep_since: Timestamp, at: Timestamp },
+    // Repaired by ignoring the sleep
+    SleepBeforeShift { at: Timestamp },
+    // Guard was still asleep at the next shift change, or at the end of the log if `at` is None.
+    // Repaired by ending the nap at the shift change, or at the end of that night's analysis
+    // window if the log runs out (straight away if the window has already closed by then).
+    UnterminatedSleep { guard: usize, asleep_since: Timestamp, at: Option<Timestamp> },
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::WakeWithoutSleep { at } =>
+                write!(f, "[{}] wakes up without falling asleep", at),
+            LogError::DoubleSleep { asleep_since, at } =>
+                write!(f, "[{}] falls asleep but has been asleep since [{}]", at, asleep_since),
+            LogError::SleepBeforeShift { at } =>
+                write!(f, "[{}] falls asleep before any guard begins shift", at),
+            LogError::UnterminatedSleep { guard, asleep_since, at: Some(at) } =>
+                write!(f, "[{}] shift changes while guard #{} is asleep since [{}]", at, guard, asleep_since),
+            LogError::UnterminatedSleep { guard, asleep_since, at: None } =>
+                write!(f, "log ends while guard #{} is asleep since [{}]", guard, asleep_since),
+        }
+    }
+}
+
+// Asleep from `start` up to but not including `end`
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Nap {
+    pub start : Timestamp,
+    pub end : Timestamp,
+}
+
+impl Nap {
+    pub fn length(&self) -> usize {
+        self.end.to_minutes() - self.start.to_minutes()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Shift {
+    pub guard : usize,
+    pub began : Timestamp,
+    pub naps : Vec<Nap>,
+}
+
+impl Shift {
+    // Midnight of the night this shift covers
+    pub fn night(&self) -> Timestamp {
+        self.began.night()
+    }
+}
+
+#[derive(Debug)]
+pub struct Interpretation {
+    pub shifts : Vec<Shift>,
+    // Everything lenient mode had to fix up. Always empty in strict mode.
+    pub repairs : Vec<LogError>,
+}
+
+#[derive(Copy, Clone)]
+enum State {
+    NoShift,
+    Awake,
+    Asleep(Timestamp),
+}
+
+fn close_nap(shift: &mut Shift, since: &Timestamp, at: Option<&Timestamp>, window: &Window) {
+    let end = match at {
+        Some(at) => *at,
+        None => std::cmp::max(window.end(&shift.night()), *since),
+    };
+    shift.naps.push(Nap { start: *since, end });
+}
+
+// Runs the (already sorted) events through the guard state machine. In strict mode every
+// malformed sequence is reported; in lenient mode they are repaired and the log is kept.
+pub fn interpret(events: &[Event], mode: Mode) -> Result<Interpretation, Vec<LogError>> {
+    interpret_with_window(events, mode, &Window::midnight_hour())
+}
+
+// As `interpret`, with a nap still going when the log ends lasting until `window` closes
+pub fn interpret_with_window(events: &[Event], mode: Mode, window: &Window) -> Result<Interpretation, Vec<LogError>> {
+    let mut shifts : Vec<Shift> = vec![];
+    let mut errors : Vec<LogError> = vec![];
+    let mut state = State::NoShift;
+
+    for event in events.iter() {
+        match (&event.event, state) {
+            (EventType::ShiftChange(id), _) => {
+                if let State::Asleep(since) = state {
+                    let shift = shifts.last_mut().unwrap();
+                    errors.push(LogError::UnterminatedSleep {
+                        guard: shift.guard,
+                        asleep_since: since,
+                        at: Some(event.stamp),
+                    });
+                    close_nap(shift, &since, Some(&event.stamp), window);
+                }
+                shifts.push(Shift { guard: *id, began: event.stamp, naps: vec![] });
+                state = State::Awake;
+            },
+            (EventType::Sleep, State::NoShift) => {
+                errors.push(LogError::SleepBeforeShift { at: event.stamp });
+            },
+            (EventType::Sleep, State::Awake) => {
+                state = State::Asleep(event.stamp);
+            },
+            (EventType::Sleep, State::Asleep(since)) => {
+                errors.push(LogError::DoubleSleep { asleep_since: since, at: event.stamp });
+            },
+            (EventType::Wake, State::Asleep(since)) => {
+                let shift = shifts.last_mut().unwrap();
+                shift.naps.push(Nap { start: since, end: event.stamp });
+                state = State::Awake;
+            },
+            (EventType::Wake, _) => {
+                errors.push(LogError::WakeWithoutSleep { at: event.stamp });
+            },
+        };
+    }
+
+    if let State::Asleep(since) = state {
+        let shift = shifts.last_mut().unwrap();
+        errors.push(LogError::UnterminatedSleep { guard: shift.guard, asleep_since: since, at: None });
+        close_nap(shift, &since, None, window);
+    }
+
+    match mode {
+        Mode::Strict if !errors.is_empty() => Err(errors),
+        _ => Ok(Interpretation { shifts, repairs: errors }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Event;
+    use crate::shifts::{interpret, interpret_with_window, LogError, Mode, Shift};
+
+    fn events(lines: &[&str]) -> Vec<Event> {
+        let mut events : Vec<_> = lines.iter().map(|s| Event::from_string(s).unwrap()).collect();
+        events.sort_unstable();
+        events
+    }
+
+    // (start minute, length) of each nap
+    fn naps(shift: &Shift) -> Vec<(usize, usize)> {
+        shift.naps.iter().map(|nap| (nap.start.minute, nap.length())).collect()
+    }
+
+    #[test]
+    fn well_formed() {
+        let log = events(&[
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:25] wakes up",
+            "[1518-11-01 23:58] Guard #99 begins shift",
+            "[1518-11-02 00:40] falls asleep",
+            "[1518-11-02 00:50] wakes up",
+        ]);
+        let result = interpret(&log, Mode::Strict).unwrap();
+        assert_eq!(result.shifts.len(), 2);
+        assert_eq!(naps(&result.shifts[0]), vec![(5, 20)]);
+        assert_eq!(result.shifts[1].guard, 99);
+        assert_eq!(naps(&result.shifts[1]), vec![(40, 10)]);
+        assert!(result.repairs.is_empty());
+    }
+
+    #[test]
+    fn strict_reports_everything() {
+        let log = events(&[
+            "[1518-11-01 00:01] falls asleep",
+            "[1518-11-01 00:02] Guard #10 begins shift",
+            "[1518-11-01 00:03] wakes up",
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:07] falls asleep",
+            "[1518-11-01 23:58] Guard #99 begins shift",
+        ]);
+        let errors = interpret(&log, Mode::Strict).unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert_eq!(errors[0], LogError::SleepBeforeShift { at: log[0].stamp });
+        assert_eq!(errors[1], LogError::WakeWithoutSleep { at: log[2].stamp });
+        assert_eq!(errors[2], LogError::DoubleSleep { asleep_since: log[3].stamp, at: log[4].stamp });
+        assert_eq!(errors[3], LogError::UnterminatedSleep {
+            guard: 10,
+            asleep_since: log[3].stamp,
+            at: Some(log[5].stamp),
+        });
+    }
+
+    #[test]
+    fn lenient_repairs() {
+        let log = events(&[
+            "[1518-11-01 00:00] Guard #10 begins shift",
+            "[1518-11-01 00:05] falls asleep",
+            "[1518-11-01 00:07] falls asleep",
+            "[1518-11-01 00:25] wakes up",
+            "[1518-11-01 00:30] wakes up",
+            "[1518-11-01 00:45] falls asleep",
+        ]);
+        let result = interpret(&log, Mode::Lenient).unwrap();
+        assert_eq!(naps(&result.shifts[0]), vec![(5, 20), (45, 15)]);
+        assert_eq!(result.repairs.len(), 3);
+        assert_eq!(result.repairs[2], LogError::UnterminatedSleep {
+            guard: 10,
+            asleep_since: log[5].stamp,
+            at: None,
+        });
+    }
+
+    #[test]
+    fn naps_span_hours() {
+        let log = events(&[
+            "[1518-11-30 22:00] Guard #10 begins shift",
+            "[1518-11-30 23:50] falls asleep",
+            "[1518-12-01 01:10] wakes up",
+            "[1518-12-01 01:20] falls asleep",
+            "[1518-12-01 01:25] Guard #99 begins shift",
+        ]);
+        let result = interpret(&log, Mode::Lenient).unwrap();
+        assert_eq!(naps(&result.shifts[0]), vec![(50, 80), (20, 5)]);
+    }
+
+    #[test]
+    fn unterminated_naps_last() {
+        // Asleep until the next guard turns up, however long that is
+        let log = events(&[
+            "[1518-11-30 22:00] Guard #10 begins shift",
+            "[1518-11-30 23:50] falls asleep",
+            "[1518-12-01 01:25] Guard #99 begins shift",
+            "[1518-12-01 01:30] falls asleep",
+        ]);
+        let result = interpret(&log, Mode::Lenient).unwrap();
+        assert_eq!(naps(&result.shifts[0]), vec![(50, 95)]);
+        // The midnight hour is already over by the time #99 falls asleep
+        assert_eq!(naps(&result.shifts[1]), vec![(30, 0)]);
+
+        // or until the window closes if the log runs out first
+        let window = "23:00-06:00".parse().unwrap();
+        let result = interpret_with_window(&log, Mode::Lenient, &window).unwrap();
+        assert_eq!(naps(&result.shifts[1]), vec![(30, 270)]);
+    }
+}
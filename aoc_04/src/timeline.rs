@@ -0,0 +1,145 @@
+use std::io::{Write, Error};
+use crate::Timestamp;
+use crate::shifts::Shift;
+use crate::window::Window;
+
+// One line of the classic per-night view: who was on duty and which minutes of the window
+// they spent asleep.
+#[derive(Debug, Clone)]
+pub struct Night {
+    pub date : Timestamp,
+    pub guard : usize,
+    pub asleep : Vec<bool>,
+}
+
+impl Night {
+    pub fn from_shift(shift: &Shift, window: &Window) -> Self {
+        let date = shift.night();
+        let mut asleep = vec![false; window.length];
+        for nap in shift.naps.iter() {
+            if let Some(range) = window.clip(&date, nap) {
+                for minute in asleep[range].iter_mut() {
+                    *minute = true;
+                }
+            }
+        }
+
+        Self {
+            date,
+            guard: shift.guard,
+            asleep,
+        }
+    }
+
+    pub fn minutes_asleep(&self) -> usize {
+        self.asleep.iter().filter(|m| **m).count()
+    }
+
+    // '.' for awake, '#' for asleep
+    pub fn row(&self) -> String {
+        self.asleep.iter().map(|m| if *m { '#' } else { '.' }).collect()
+    }
+}
+
+pub fn nights(shifts: &[Shift], window: &Window) -> Vec<Night> {
+    shifts.iter().map(|shift| Night::from_shift(shift, window)).collect()
+}
+
+pub fn render(nights: &[Night], window: &Window) -> String {
+    let clock : Vec<(usize, usize)> = (0..window.length).map(|i| window.clock(i)).collect();
+    let tens : String = clock.iter().map(|(_, m)| (b'0' + (m / 10) as u8) as char).collect();
+    let ones : String = clock.iter().map(|(_, m)| (b'0' + (m % 10) as u8) as char).collect();
+
+    let mut out = String::new();
+    out.push_str("Date   ID      Minute\n");
+    if clock.iter().any(|(h, _)| *h != clock[0].0) {
+        // Label the start of each hour when the window covers more than one
+        let mut hours = vec![' '; clock.len() + 1];
+        for (i, (h, m)) in clock.iter().enumerate() {
+            if i == 0 || *m == 0 {
+                let label = format!("{:02}", h);
+                hours[i] = label.as_bytes()[0] as char;
+                hours[i + 1] = label.as_bytes()[1] as char;
+            }
+        }
+        out.push_str(&format!("               {}\n", hours.iter().collect::<String>().trim_end()));
+    }
+    out.push_str(&format!("               {}\n", tens));
+    out.push_str(&format!("               {}\n", ones));
+    for night in nights.iter() {
+        let id = format!("#{}", night.guard);
+        out.push_str(&format!("{:02}-{:02}  {:<6}  {}\n", night.date.month, night.date.day, id, night.row()));
+    }
+    out
+}
+
+pub fn write_csv<W: Write>(nights: &[Night], w: &mut W) -> Result<(), Error> {
+    writeln!(w, "date,guard,minutes_asleep,row")?;
+    for night in nights.iter() {
+        writeln!(w, "{:04}-{:02}-{:02},{},{},{}", night.date.year, night.date.month, night.date.day,
+                 night.guard, night.minutes_asleep(), night.row())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Timestamp;
+    use crate::shifts::{Nap, Shift};
+    use crate::timeline::{nights, render, write_csv};
+    use crate::window::Window;
+
+    fn nap(night: &Timestamp, start: usize, length: usize) -> Nap {
+        Nap { start: night.add_minutes(start), end: night.add_minutes(start + length) }
+    }
+
+    fn example() -> Vec<Shift> {
+        let first = Timestamp::new(1518, 11, 1, 0, 0);
+        let second = Timestamp::new(1518, 12, 1, 0, 0);
+        vec![
+            Shift { guard: 10, began: first, naps: vec![nap(&first, 5, 20), nap(&first, 30, 25)] },
+            Shift { guard: 99, began: Timestamp::new(1518, 11, 30, 23, 58), naps: vec![nap(&second, 40, 10)] },
+        ]
+    }
+
+    #[test]
+    fn rows() {
+        let nights = nights(&example(), &Window::midnight_hour());
+        assert_eq!(nights[0].row(),
+                   ".....####################.....#########################.....");
+        assert_eq!(nights[0].minutes_asleep(), 45);
+        // Came in before midnight on the last day of the month
+        assert_eq!(nights[1].date, Timestamp::new(1518, 12, 1, 0, 0));
+    }
+
+    #[test]
+    fn ascii() {
+        let text = render(&nights(&example(), &Window::midnight_hour()), &Window::midnight_hour());
+        let lines : Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], "               000000000011111111112222222222333333333344444444445555555555");
+        assert_eq!(lines[3], "11-01  #10     .....####################.....#########################.....");
+        assert_eq!(lines[4], "12-01  #99     ........................................##########..........");
+    }
+
+    #[test]
+    fn csv() {
+        let mut out : Vec<u8> = vec![];
+        write_csv(&nights(&example(), &Window::midnight_hour()), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines : Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "date,guard,minutes_asleep,row");
+        assert_eq!(lines[2], "1518-12-01,99,10,........................................##########..........");
+    }
+
+    #[test]
+    fn wider_window() {
+        let window = Window::new(-3, 6);
+        let text = render(&nights(&example(), &window), &window);
+        let lines : Vec<&str> = text.lines().collect();
+        assert_eq!(lines[1], "               23 00");
+        assert_eq!(lines[2], "               555000");
+        assert_eq!(lines[3], "               789012");
+        assert_eq!(lines[4], "11-01  #10     ......");
+    }
+}
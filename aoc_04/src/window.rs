@@ -0,0 +1,144 @@
+use std::cmp::{max, min};
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use std::ops::Range;
+use std::str::FromStr;
+use crate::Timestamp;
+use crate::shifts::Nap;
+
+const MINS_PER_DAY: isize = 24 * 60;
+
+// The stretch of each night to analyse, in minutes relative to that night's midnight. A
+// negative start reaches back into the evening before.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Window {
+    pub start : isize,
+    pub length : usize,
+}
+
+impl Window {
+    pub fn new(start: isize, length: usize) -> Self {
+        Self { start, length }
+    }
+
+    // 00:00 - 01:00, the only hour the original puzzle cares about
+    pub fn midnight_hour() -> Self {
+        Self::new(0, 60)
+    }
+
+    // The part of the nap that falls in this window on the night starting at `midnight`, as
+    // indices into the window
+    pub fn clip(&self, midnight: &Timestamp, nap: &Nap) -> Option<Range<usize>> {
+        let base = midnight.to_minutes() as isize + self.start;
+        let start = max(nap.start.to_minutes() as isize - base, 0);
+        let end = min(nap.end.to_minutes() as isize - base, self.length as isize);
+        if start < end {
+            Some(start as usize..end as usize)
+        } else {
+            None
+        }
+    }
+
+    // When the window closes on the night starting at `midnight`
+    pub fn end(&self, midnight: &Timestamp) -> Timestamp {
+        Timestamp::from_minutes((midnight.to_minutes() as isize + self.start + self.length as isize) as usize)
+    }
+
+    // (hour, minute) on the clock for an index into the window
+    pub fn clock(&self, index: usize) -> (usize, usize) {
+        let offset = (self.start + index as isize).rem_euclid(MINS_PER_DAY) as usize;
+        (offset / 60, offset % 60)
+    }
+
+    // Minutes since midnight
+    fn parse_clock(s: &str) -> Result<isize, Error> {
+        let parts : Vec<&str> = s.trim().split(':').collect();
+        if parts.len() != 2 {
+            return Err(Error::new(ErrorKind::InvalidInput, "Expected HH:MM"));
+        }
+        let hour : isize = parts[0].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let minute : isize = parts[1].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        if !(0..24).contains(&hour) || !(0..60).contains(&minute) {
+            return Err(Error::new(ErrorKind::InvalidInput, "Not a valid time of day"));
+        }
+        Ok(hour * 60 + minute)
+    }
+}
+
+impl Default for Window {
+    fn default() -> Self {
+        Self::midnight_hour()
+    }
+}
+
+// "HH:MM-HH:MM", e.g. "23:00-06:00" for a whole overnight shift. A window that wraps past
+// midnight starts on the evening before the night, and so does one that starts at noon or
+// later, since that's when the shift begins. Anything else ("11:00-13:00") is the day after.
+impl FromStr for Window {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts : Vec<&str> = s.split('-').collect();
+        if parts.len() != 2 {
+            return Err(Error::new(ErrorKind::InvalidInput, "Expected HH:MM-HH:MM"));
+        }
+        let start = Self::parse_clock(parts[0])?;
+        let end = Self::parse_clock(parts[1])?;
+        if end == start {
+            return Err(Error::new(ErrorKind::InvalidInput, "Window is empty"));
+        }
+
+        let length = (end - start).rem_euclid(MINS_PER_DAY);
+        let start = if end < start || start >= 12 * 60 { start - MINS_PER_DAY } else { start };
+        Ok(Self::new(start, length as usize))
+    }
+}
+
+impl fmt::Display for Window {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (start_hour, start_minute) = self.clock(0);
+        let (end_hour, end_minute) = self.clock(self.length);
+        write!(f, "{:02}:{:02}-{:02}:{:02}", start_hour, start_minute, end_hour, end_minute)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Timestamp;
+    use crate::shifts::Nap;
+    use crate::window::Window;
+
+    #[test]
+    fn parse() {
+        assert_eq!("00:00-01:00".parse::<Window>().unwrap(), Window::midnight_hour());
+        assert_eq!("23:00-06:00".parse::<Window>().unwrap(), Window::new(-60, 420));
+        assert_eq!("23:30-00:00".parse::<Window>().unwrap(), Window::new(-30, 30));
+        assert_eq!("06:00-05:00".parse::<Window>().unwrap(), Window::new(-18 * 60, 23 * 60));
+        assert_eq!("20:00-22:00".parse::<Window>().unwrap(), Window::new(-4 * 60, 120));
+        assert!("05:00-05:00".parse::<Window>().is_err());
+        assert!("25:00-26:00".parse::<Window>().is_err());
+        assert_eq!(Window::new(-60, 420).to_string(), "23:00-06:00");
+    }
+
+    #[test]
+    fn across_noon() {
+        let window : Window = "11:00-13:00".parse().unwrap();
+        assert_eq!(window, Window::new(11 * 60, 120));
+        assert_eq!(window.clock(60), (12, 0));
+        assert_eq!(window.to_string(), "11:00-13:00");
+    }
+
+    #[test]
+    fn clip_across_midnight() {
+        let midnight = Timestamp::new(1518, 12, 1, 0, 0);
+        let nap = Nap {
+            start: Timestamp::new(1518, 11, 30, 23, 50),
+            end: Timestamp::new(1518, 12, 1, 1, 10),
+        };
+        assert_eq!(nap.length(), 80);
+        assert_eq!(Window::midnight_hour().clip(&midnight, &nap), Some(0..60));
+        assert_eq!(Window::new(-60, 420).clip(&midnight, &nap), Some(50..130));
+        assert_eq!(Window::new(120, 60).clip(&midnight, &nap), None);
+        assert_eq!(Window::new(-60, 420).clock(50), (23, 50));
+    }
+}
@@ -0,0 +1,94 @@
+use std::io::{Read, BufReader, BufRead};
+
+pub mod parallel;
+pub mod reaction;
+pub mod search;
+pub mod stream;
+pub mod trace;
+
+use crate::reaction::Reaction;
+
+pub fn read<R: Read>(io: R) -> Vec<String> {
+    let br = BufReader::new(io);
+    br.lines().map(|l| l.unwrap()).collect()
+}
+
+// Reduces the polymer under the given rule, skipping every unit of the same type as `ignore`
+pub fn reduce<R: Reaction + ?Sized>(vec: &[char], ignore: Option<char>, rule: &R) -> Vec<char> {
+    let mut working : Vec<char> = vec![];
+    let mut top : Option<char> = None;
+
+    let ig_type = ignore.map(|ch| rule.unit_type(ch));
+
+    for ch_ref in vec.iter() {
+        let ch = *ch_ref;
+        if ig_type.is_some() && ig_type == Some(rule.unit_type(ch)) {
+            continue;
+        }
+        match top {
+            Some(ch_val) => {
+                if rule.reacts(ch_val, ch) {
+                    // Pop top
+                    working.pop();
+                    if !working.is_empty() {
+                        top = Some(working[working.len() - 1]);
+                    }
+                    else {
+                        top = None;
+                    }
+                }
+                else {
+                    working.push(ch);
+                    top = Some(ch);
+                }
+            }
+            None => {
+                top = Some(ch);
+                working.push(ch);
+            }
+        }
+    }
+
+    working
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reduce;
+    use crate::reaction::{CasePair, PairTable};
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    fn string(v: Vec<char>) -> String {
+        v.into_iter().collect()
+    }
+
+    #[test]
+    fn example() {
+        let polymer = chars("dabAcCaCBAcCcaDA");
+        assert_eq!(string(reduce(&polymer, None, &CasePair)), "dabCBAcaDA");
+        assert_eq!(string(reduce(&polymer, Some('c'), &CasePair)), "daDA");
+        assert_eq!(string(reduce(&polymer, Some('A'), &CasePair)), "dbCBcD");
+    }
+
+    #[test]
+    fn punctuation_does_not_react() {
+        // '@' and '`' are 32 apart but aren't a case pair
+        assert_eq!(string(reduce(&chars("a@`A"), None, &CasePair)), "a@`A");
+        assert_eq!(string(reduce(&chars("xΣσy"), None, &CasePair)), "xy");
+    }
+
+    #[test]
+    fn custom_rules() {
+        let table = PairTable::new(&[('(', ')'), ('[', ']')]);
+        assert_eq!(string(reduce(&chars("([)]]"), None, &table)), "([)]]");
+        assert_eq!(string(reduce(&chars("([]())x"), None, &table)), "x");
+        assert_eq!(string(reduce(&chars("(([]x"), Some(')'), &table)), "x");
+
+        let digits = |a: char, b: char| a.is_ascii_digit() && b.is_ascii_digit()
+            && a.to_digit(10).unwrap() + b.to_digit(10).unwrap() == 10;
+        assert_eq!(string(reduce(&chars("1928375"), None, &digits)), "5");
+    }
+}
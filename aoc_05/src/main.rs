@@ -1,75 +1,68 @@
-
 use std::fs::File;
-use std::io::{Read, BufReader, BufRead, Error, ErrorKind};
-
-fn read<R: Read>(io: R) -> Vec<String> {
-    let br = BufReader::new(io);
-    br.lines().map(|l| l.unwrap()).collect()
-}
-
-fn reduce(vec: &Vec<char>, ignore: Option<char>) -> Vec<char> {
-    let mut working : Vec<char> = vec![];
-    let mut top : Option<char> = None;
+use std::io::BufWriter;
 
-    let ig_ch = ignore.unwrap_or('\0');
+use aoc_05::{read, parallel, search, stream, trace};
+use aoc_05::reaction::CasePair;
 
-    for ch_ref in vec.iter() {
-        let ch = *ch_ref;
-        if ch == ig_ch || ch == ig_ch.to_ascii_uppercase() {
-            continue;
-        }
-        match top {
-            Some(ch_val) => {
-                let val = ch_val as isize;
-                let curr_val = ch as isize;
-                if val - 32 == curr_val || val + 32 == curr_val {
-                    // Pop top
-                    working.pop();
-                    if !working.is_empty() {
-                        top = Some(working[working.len() - 1]);
-                    }
-                    else {
-                        top = None;
-                    }
-                }
-                else {
-                    working.push(ch);
-                    top = Some(ch);
-                }
-            }
-            None => {
-                top = Some(ch);
-                working.push(ch);
-            }
-        }
-    }
+// Reduces a polymer of any size without loading it into memory as chars
+fn stream(path: &str, out_path: Option<&String>) -> std::io::Result<()> {
+    let input = File::open(path)?;
+    let len = match out_path {
+        Some(out_path) => {
+            let mut out = BufWriter::new(File::create(out_path)?);
+            stream::reduce_reader(input, Some(&mut out), None, &CasePair)?
+        },
+        None => stream::reduce_reader(input, None::<&mut File>, None, &CasePair)?,
+    };
 
-    working
+    println!("Length after reduction is {}", len);
+    Ok(())
 }
 
 fn main() -> std::io::Result<()> {
+    let args : Vec<String> = std::env::args().skip(1).collect();
+    let arg_value = |flag: &str| args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1))
+        .filter(|value| !value.starts_with("--"));
+    if args.iter().any(|arg| arg == "--stream") {
+        let path = arg_value("--stream").map_or("./input.txt", |p| p.as_str());
+        return stream(path, arg_value("--out"));
+    }
+
     let f = File::open("./input.txt")?;
-    let mut v = read(f);
+    let v = read(f);
     assert!(v.len() == 1);
 
-    let mut s : Vec<char> = v[0].chars().collect();
-    let mut reduced = reduce(&s, None);
+    let s : Vec<char> = v[0].chars().collect();
+    let reduced = parallel::reduce_parallel(&s, None, &CasePair, search::default_workers());
 
     println!("Original length is {}", s.len());
     println!("Length after initial reduction is {}", reduced.len());
 
-    let mut min = reduced.len();
-    let mut min_char = 'a';
-    for ch in (b'a'..=b'z').map(char::from) {
-        let reduced_by_ch = reduce(&reduced, Some(ch));
-        if reduced_by_ch.len() < min {
-            min = reduced_by_ch.len();
-            min_char = ch;
+    if args.iter().any(|arg| arg == "--trace") {
+        let trace = trace::reduce_traced(&s, None, &CasePair);
+        for pair in trace.reactions.iter() {
+            println!("{}{} at {}-{}, depth {}", s[pair.left], s[pair.right], pair.left, pair.right, pair.depth);
         }
-        println!("On letter {}, len: {}", ch, reduced_by_ch.len());
+        println!("{} reactions, max depth {}", trace.reactions.len(), trace.max_depth().unwrap_or(0));
+        println!("Survivors: {:?}", trace.survivors);
     }
 
-    println!("Best reduction is {} at {}", min_char, min);
+    // Ranking the reduced polymer gives the same lengths and is much quicker. Types that all
+    // reacted away in the first reduction aren't in it, but taking them out changes nothing.
+    let mut ranking = search::rank_removals(&reduced, &CasePair, search::default_workers());
+    for unit in search::unit_types(&s, &CasePair) {
+        if !ranking.iter().any(|(u, _)| *u == unit) {
+            ranking.push((unit, reduced.len()));
+        }
+    }
+    ranking.sort_unstable_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    for (unit, len) in ranking.iter() {
+        println!("On unit {}, len: {}", unit, len);
+    }
+
+    if let Some((unit, len)) = ranking.first() {
+        println!("Best reduction is {} at {}", unit, len);
+    }
 
     Ok(())
 }
@@ -0,0 +1,107 @@
+use std::thread;
+use crate::reduce;
+use crate::reaction::Reaction;
+
+// Appends an already reduced chunk onto an already reduced polymer. Only the boundary can
+// react: once a unit of `right` survives, everything after it sits next to a unit it was
+// already next to.
+pub fn merge_reduced<R: Reaction + ?Sized>(left: &mut Vec<char>, right: &[char], rule: &R) {
+    let mut j = 0;
+    while j < right.len() {
+        match left.last() {
+            Some(top) if rule.reacts(*top, right[j]) => {
+                left.pop();
+                j += 1;
+            },
+            _ => break,
+        }
+    }
+    left.extend_from_slice(&right[j..]);
+}
+
+// Splits the polymer into one chunk per worker, reduces them in parallel and merges the
+// results. Where the chunks split only leaves `reduce`'s answer alone for one-to-one rules, so
+// anything else is just reduced in one go.
+pub fn reduce_parallel<R: Reaction + Sync + ?Sized>(vec: &[char], ignore: Option<char>, rule: &R, workers: usize)
+    -> Vec<char> {
+    if !rule.one_to_one() {
+        return reduce(vec, ignore, rule);
+    }
+    let chunk_size = std::cmp::max(vec.len().div_ceil(std::cmp::max(workers, 1)), 1);
+
+    let chunks : Vec<Vec<char>> = thread::scope(|scope| {
+        let handles : Vec<_> = vec.chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || reduce(chunk, ignore, rule)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+
+    let mut reduced : Vec<char> = vec![];
+    for chunk in chunks.iter() {
+        merge_reduced(&mut reduced, chunk, rule);
+    }
+    reduced
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reduce;
+    use crate::reaction::{CasePair, PairTable};
+    use crate::parallel::{merge_reduced, reduce_parallel};
+
+    // Cheap deterministic polymer with plenty of reactions: only a few unit types
+    fn polymer(len: usize, seed: u64) -> Vec<char> {
+        let mut state = seed;
+        (0..len).map(|_| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let bits = (state >> 33) as u8;
+            let ch = (b'a' + bits % 3) as char;
+            if bits & 0x80 != 0 { ch.to_ascii_uppercase() } else { ch }
+        }).collect()
+    }
+
+    #[test]
+    fn merge_boundary() {
+        let mut left : Vec<char> = "dabc".chars().collect();
+        let right : Vec<char> = "CBxA".chars().collect();
+        merge_reduced(&mut left, &right, &CasePair);
+        assert_eq!(left.into_iter().collect::<String>(), "daxA");
+    }
+
+    #[test]
+    fn matches_reduce() {
+        for seed in 0..20 {
+            let polymer = polymer(1000 + seed as usize * 37, seed);
+            let expected = reduce(&polymer, None, &CasePair);
+            for workers in [1, 2, 3, 7, 16, 5000].iter() {
+                assert_eq!(reduce_parallel(&polymer, None, &CasePair, *workers), expected);
+            }
+            assert_eq!(reduce_parallel(&polymer, Some('b'), &CasePair, 4), reduce(&polymer, Some('b'), &CasePair));
+        }
+        assert!(reduce_parallel(&[], None, &CasePair, 4).is_empty());
+    }
+
+    #[test]
+    fn matches_reduce_with_pair_tables() {
+        let chars = |s: &str| s.chars().collect::<Vec<char>>();
+
+        // b can go with either neighbour, and splitting "cc|ba" would take the wrong one
+        let chain = PairTable::new(&[('a', 'b'), ('b', 'c')]);
+        assert_eq!(reduce(&chars("ccba"), None, &chain), chars("ca"));
+        assert_eq!(reduce_parallel(&chars("ccba"), None, &chain, 2), chars("ca"));
+
+        let brackets = PairTable::new(&[('(', ')'), ('[', ']')]);
+        for (i, table) in [chain, brackets].iter().enumerate() {
+            for seed in 0..10 {
+                let polymer : Vec<char> = polymer(500, seed).into_iter().map(|ch| match (i, ch) {
+                    (0, 'A') | (0, 'B') | (0, 'C') => ch.to_ascii_lowercase(),
+                    (1, 'a') => '(', (1, 'A') => ')', (1, 'b') => '[', (1, 'B') => ']',
+                    _ => ch,
+                }).collect();
+                for workers in [2, 3, 16].iter() {
+                    assert_eq!(reduce_parallel(&polymer, None, table, *workers), reduce(&polymer, None, table));
+                }
+            }
+        }
+    }
+}
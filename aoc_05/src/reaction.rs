@@ -0,0 +1,131 @@
+use std::collections::{HashMap, HashSet};
+
+// Decides which adjacent units annihilate each other
+pub trait Reaction {
+    fn reacts(&self, a: char, b: char) -> bool;
+
+    // Units of the same type are removed together when trying out removals. By default every
+    // unit is its own type.
+    fn unit_type(&self, ch: char) -> char {
+        ch
+    }
+
+    // Whether every unit reacts with at most one other unit, and that one with it. Then the
+    // order reactions happen in can't change the result, so a polymer can be reduced in
+    // pieces. Rules that can't promise that say no.
+    fn one_to_one(&self) -> bool {
+        false
+    }
+}
+
+// Any closure works as a rule, e.g. `|a, b| a == b`
+impl<F: Fn(char, char) -> bool> Reaction for F {
+    fn reacts(&self, a: char, b: char) -> bool {
+        self(a, b)
+    }
+}
+
+fn single_lowercase(ch: char) -> char {
+    let mut lower = ch.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) => l,
+        _ => ch,
+    }
+}
+
+// The puzzle's rule: the same letter in opposite cases reacts. Works for any cased alphabet,
+// and things without case (digits, punctuation) never react.
+pub struct CasePair;
+
+impl Reaction for CasePair {
+    fn reacts(&self, a: char, b: char) -> bool {
+        a != b && single_lowercase(a) == single_lowercase(b)
+    }
+
+    fn unit_type(&self, ch: char) -> char {
+        single_lowercase(ch)
+    }
+
+    fn one_to_one(&self) -> bool {
+        true
+    }
+}
+
+// An explicit list of pairs that annihilate, in either order. Units linked by pairs, directly
+// or through others, share a type (the smallest of them), so removing one removes them all.
+pub struct PairTable {
+    pairs : HashSet<(char, char)>,
+    types : HashMap<char, char>,
+}
+
+impl PairTable {
+    pub fn new(pairs: &[(char, char)]) -> Self {
+        let mut table = Self {
+            pairs: HashSet::new(),
+            types: HashMap::new(),
+        };
+        for (a, b) in pairs.iter() {
+            table.insert(*a, *b);
+        }
+        table
+    }
+
+    pub fn insert(&mut self, a: char, b: char) {
+        self.pairs.insert((a, b));
+        self.pairs.insert((b, a));
+        // Merge the two types, relabelling everything already in the bigger one
+        let (ta, tb) = (self.unit_type(a), self.unit_type(b));
+        let (smallest, largest) = (std::cmp::min(ta, tb), std::cmp::max(ta, tb));
+        for t in self.types.values_mut() {
+            if *t == largest {
+                *t = smallest;
+            }
+        }
+        self.types.insert(a, smallest);
+        self.types.insert(b, smallest);
+    }
+}
+
+impl Reaction for PairTable {
+    fn reacts(&self, a: char, b: char) -> bool {
+        self.pairs.contains(&(a, b))
+    }
+
+    fn unit_type(&self, ch: char) -> char {
+        *self.types.get(&ch).unwrap_or(&ch)
+    }
+
+    fn one_to_one(&self) -> bool {
+        // Pairs are stored both ways round, so each unit's partners show up as firsts
+        let mut partners : HashMap<char, usize> = HashMap::new();
+        for (a, _) in self.pairs.iter() {
+            *partners.entry(*a).or_default() += 1;
+        }
+        partners.values().all(|n| *n == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reaction::{PairTable, Reaction};
+
+    #[test]
+    fn types_are_transitive() {
+        for pairs in [[('b', 'c'), ('a', 'b')], [('a', 'b'), ('b', 'c')]].iter() {
+            let table = PairTable::new(pairs);
+            assert_eq!(table.unit_type('a'), 'a');
+            assert_eq!(table.unit_type('b'), 'a');
+            assert_eq!(table.unit_type('c'), 'a');
+            assert!(table.reacts('c', 'b'));
+            assert!(!table.reacts('a', 'c'));
+            assert!(!table.one_to_one());
+        }
+        assert!(PairTable::new(&[('(', ')'), ('[', ']'), ('*', '*')]).one_to_one());
+
+        // Joining two existing groups relabels all of the bigger one
+        let table = PairTable::new(&[('x', 'y'), ('c', 'd'), ('d', 'y')]);
+        for ch in ['c', 'd', 'x', 'y'].iter() {
+            assert_eq!(table.unit_type(*ch), 'c');
+        }
+    }
+}
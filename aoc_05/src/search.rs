@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use crate::reduce;
+use crate::reaction::Reaction;
+
+// Every unit type that shows up in the polymer, sorted
+pub fn unit_types<R: Reaction + ?Sized>(polymer: &[char], rule: &R) -> Vec<char> {
+    let mut types : Vec<char> = polymer.iter().map(|ch| rule.unit_type(*ch)).collect();
+    types.sort_unstable();
+    types.dedup();
+    types
+}
+
+pub fn default_workers() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// Tries removing each unit type present in the polymer, spread over `workers` threads.
+// Returns (unit type, reduced length) for all of them, shortest first, ties by unit type.
+pub fn rank_removals<R: Reaction + Sync + ?Sized>(polymer: &[char], rule: &R, workers: usize) -> Vec<(char, usize)> {
+    let types = unit_types(polymer, rule);
+    let next = AtomicUsize::new(0);
+
+    let mut ranking : Vec<(char, usize)> = thread::scope(|scope| {
+        let handles : Vec<_> = (0..std::cmp::max(workers, 1)).map(|_| {
+            scope.spawn(|| {
+                let mut results = vec![];
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= types.len() {
+                        break;
+                    }
+                    results.push((types[i], reduce(polymer, Some(types[i]), rule).len()));
+                }
+                results
+            })
+        }).collect();
+
+        handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+    });
+
+    ranking.sort_unstable_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    ranking
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reaction::CasePair;
+    use crate::search::{rank_removals, unit_types};
+
+    #[test]
+    fn example() {
+        let polymer : Vec<char> = "dabAcCaCBAcCcaDA".chars().collect();
+        assert_eq!(unit_types(&polymer, &CasePair), vec!['a', 'b', 'c', 'd']);
+        for workers in 1..=5 {
+            assert_eq!(rank_removals(&polymer, &CasePair, workers),
+                       vec![('c', 4), ('a', 6), ('d', 6), ('b', 8)]);
+        }
+    }
+
+    #[test]
+    fn beyond_ascii_letters() {
+        let polymer : Vec<char> = "1Σ2σΣ3".chars().collect();
+        assert_eq!(rank_removals(&polymer, &CasePair, 2),
+                   vec![('1', 3), ('2', 3), ('3', 3), ('σ', 3)]);
+    }
+}
@@ -0,0 +1,179 @@
+use std::io::{Read, Write, Error, ErrorKind};
+use crate::reaction::Reaction;
+
+const CHUNK_SIZE: usize = 1 << 16;
+
+// Reduces a polymer a byte at a time with a single stack, for inputs too big to hold as
+// `Vec<char>`. Only ASCII bytes take part in reactions; anything else (e.g. the pieces of a
+// multibyte UTF-8 char) is kept as an inert unit, so it never reacts and is never ignored.
+// Whitespace is trimmed from both ends, as `reduce` expects of its input, but kept as units
+// in the middle. The rule is evaluated once per byte pair up front, so feeding is just table
+// lookups.
+pub struct ByteReducer {
+    reacts : Vec<bool>,
+    skip : [bool; 128],
+    stack : Vec<u8>,
+    // Whitespace seen since the last unit, only kept if another unit turns up
+    pending : Vec<u8>,
+    started : bool,
+}
+
+fn is_space(b: u8) -> bool {
+    b.is_ascii() && (b as char).is_whitespace()
+}
+
+impl ByteReducer {
+    pub fn new<R: Reaction + ?Sized>(rule: &R, ignore: Option<char>) -> Self {
+        let mut reacts = vec![false; 128 * 128];
+        for a in 0..128u8 {
+            for b in 0..128u8 {
+                reacts[a as usize * 128 + b as usize] = rule.reacts(a as char, b as char);
+            }
+        }
+
+        let ig_type = ignore.map(|ch| rule.unit_type(ch));
+        let mut skip = [false; 128];
+        for (b, s) in skip.iter_mut().enumerate() {
+            *s = ig_type.is_some() && ig_type == Some(rule.unit_type(b as u8 as char));
+        }
+
+        Self {
+            reacts,
+            skip,
+            stack: vec![],
+            pending: vec![],
+            started: false,
+        }
+    }
+
+    fn push(&mut self, b: u8) {
+        match self.stack.last() {
+            Some(top) if top.is_ascii() && b.is_ascii() && self.reacts[*top as usize * 128 + b as usize] => {
+                self.stack.pop();
+            },
+            _ => self.stack.push(b),
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        for b in bytes.iter() {
+            if is_space(*b) {
+                if self.started {
+                    self.pending.push(*b);
+                }
+                continue;
+            }
+            self.started = true;
+            for space in std::mem::take(&mut self.pending) {
+                self.push(space);
+            }
+            if b.is_ascii() && self.skip[*b as usize] {
+                continue;
+            }
+            self.push(*b);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.stack
+    }
+}
+
+// Streams the polymer from `input`, returning the reduced length. The reduced polymer is
+// written to `output` if given. Leading and trailing whitespace (e.g. the final newline) is
+// dropped.
+pub fn reduce_reader<I, O, R>(mut input: I, output: Option<&mut O>, ignore: Option<char>, rule: &R)
+    -> Result<usize, Error>
+    where I: Read, O: Write, R: Reaction + ?Sized {
+    let mut reducer = ByteReducer::new(rule, ignore);
+    let mut buf = vec![0; CHUNK_SIZE];
+
+    loop {
+        let n = match input.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(ref e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        reducer.feed(&buf[..n]);
+    }
+
+    if let Some(out) = output {
+        out.write_all(reducer.as_bytes())?;
+        out.flush()?;
+    }
+
+    Ok(reducer.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reduce;
+    use crate::reaction::{CasePair, PairTable};
+    use crate::stream::{reduce_reader, ByteReducer};
+
+    #[test]
+    fn matches_reduce() {
+        let polymer = "dabAcCaCBAcCcaDA\n";
+        let mut out : Vec<u8> = vec![];
+        let len = reduce_reader(polymer.as_bytes(), Some(&mut out), None, &CasePair).unwrap();
+        assert_eq!(len, 10);
+        assert_eq!(out, b"dabCBAcaDA");
+
+        let len = reduce_reader(polymer.as_bytes(), None::<&mut Vec<u8>>, Some('c'), &CasePair).unwrap();
+        let chars : Vec<char> = polymer.trim().chars().collect();
+        assert_eq!(len, reduce(&chars, Some('c'), &CasePair).len());
+    }
+
+    #[test]
+    fn reacts_across_chunks() {
+        let mut reducer = ByteReducer::new(&CasePair, None);
+        reducer.feed(b"abc");
+        reducer.feed(b"CB");
+        assert_eq!(reducer.as_bytes(), b"a");
+        reducer.feed(b"A");
+        assert!(reducer.is_empty());
+    }
+
+    #[test]
+    fn only_ascii_reacts() {
+        // Taken as Latin-1, C3 and E3 are 'Ã' and 'ã'
+        let mut reducer = ByteReducer::new(&CasePair, None);
+        reducer.feed(&[0xc3, 0xe3, b'a', b'A']);
+        assert_eq!(reducer.as_bytes(), &[0xc3, 0xe3]);
+
+        // Nor are they ignored, so "Ãã" (C3 83 C3 A3 in UTF-8) comes through whole
+        let mut reducer = ByteReducer::new(&CasePair, Some('\u{e3}'));
+        reducer.feed("\u{c3}\u{e3}".as_bytes());
+        assert_eq!(reducer.as_bytes(), "\u{c3}\u{e3}".as_bytes());
+
+        // Even when the rule says they should
+        let mut reducer = ByteReducer::new(&PairTable::new(&[('\u{83}', '\u{c3}')]), None);
+        reducer.feed("\u{c3}\u{c3}".as_bytes());
+        assert_eq!(reducer.len(), 4);
+    }
+
+    #[test]
+    fn trims_like_reduce() {
+        let polymer = "  \taA b\nB\n \t";
+        let mut out : Vec<u8> = vec![];
+        let len = reduce_reader(polymer.as_bytes(), Some(&mut out), None, &CasePair).unwrap();
+        let chars : Vec<char> = polymer.trim().chars().collect();
+        let expected : String = reduce(&chars, None, &CasePair).into_iter().collect();
+        assert_eq!(out, expected.as_bytes());
+        assert_eq!(len, 4);
+
+        // Whitespace before an ignored unit isn't trailing, just as in `reduce`
+        let len = reduce_reader("ab \nc".as_bytes(), None::<&mut Vec<u8>>, Some('c'), &CasePair).unwrap();
+        let chars : Vec<char> = "ab \nc".chars().collect();
+        assert_eq!(len, reduce(&chars, Some('c'), &CasePair).len());
+    }
+}
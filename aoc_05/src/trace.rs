@@ -0,0 +1,106 @@
+use crate::reaction::Reaction;
+
+// Two input positions that annihilated. Depth 0 means they were adjacent once any ignored
+// units were dropped; otherwise it's one more than the deepest reaction between them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct ReactionPair {
+    pub left : usize,
+    pub right : usize,
+    pub depth : usize,
+}
+
+#[derive(Debug)]
+pub struct Trace {
+    pub reduced : Vec<char>,
+    // Input positions of the units in `reduced`
+    pub survivors : Vec<usize>,
+    // In the order they happened
+    pub reactions : Vec<ReactionPair>,
+    // Input positions skipped because of `ignore`
+    pub ignored : Vec<usize>,
+}
+
+impl Trace {
+    pub fn max_depth(&self) -> Option<usize> {
+        self.reactions.iter().map(|r| r.depth).max()
+    }
+}
+
+struct Entry {
+    ch : char,
+    pos : usize,
+    // Deepest reaction seen between this unit and whatever is above it
+    inner : Option<usize>,
+}
+
+// Same reduction as `reduce`, but keeping track of where everything came from
+pub fn reduce_traced<R: Reaction + ?Sized>(vec: &[char], ignore: Option<char>, rule: &R) -> Trace {
+    let mut stack : Vec<Entry> = vec![];
+    let mut reactions : Vec<ReactionPair> = vec![];
+    let mut ignored : Vec<usize> = vec![];
+
+    let ig_type = ignore.map(|ch| rule.unit_type(ch));
+
+    for (pos, ch) in vec.iter().enumerate() {
+        if ig_type.is_some() && ig_type == Some(rule.unit_type(*ch)) {
+            ignored.push(pos);
+            continue;
+        }
+
+        match stack.last() {
+            Some(top) if rule.reacts(top.ch, *ch) => {
+                let depth = top.inner.map_or(0, |d| d + 1);
+                reactions.push(ReactionPair { left: top.pos, right: pos, depth });
+                stack.pop();
+                if let Some(below) = stack.last_mut() {
+                    below.inner = std::cmp::max(below.inner, Some(depth));
+                }
+            },
+            _ => stack.push(Entry { ch: *ch, pos, inner: None }),
+        }
+    }
+
+    Trace {
+        reduced: stack.iter().map(|e| e.ch).collect(),
+        survivors: stack.iter().map(|e| e.pos).collect(),
+        reactions,
+        ignored,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::reduce;
+    use crate::reaction::CasePair;
+    use crate::trace::{reduce_traced, ReactionPair};
+
+    fn pairs(trace: &[ReactionPair]) -> Vec<(usize, usize, usize)> {
+        trace.iter().map(|r| (r.left, r.right, r.depth)).collect()
+    }
+
+    #[test]
+    fn nesting() {
+        let polymer : Vec<char> = "abBcCAd".chars().collect();
+        let trace = reduce_traced(&polymer, None, &CasePair);
+        assert_eq!(pairs(&trace.reactions), vec![(1, 2, 0), (3, 4, 0), (0, 5, 1)]);
+        assert_eq!(trace.survivors, vec![6]);
+        assert_eq!(trace.max_depth(), Some(1));
+    }
+
+    #[test]
+    fn matches_reduce() {
+        let polymer : Vec<char> = "dabAcCaCBAcCcaDA".chars().collect();
+        for ignore in [None, Some('a'), Some('c')] {
+            let trace = reduce_traced(&polymer, ignore, &CasePair);
+            assert_eq!(trace.reduced, reduce(&polymer, ignore, &CasePair));
+            let survivors : Vec<char> = trace.survivors.iter().map(|p| polymer[*p]).collect();
+            assert_eq!(survivors, trace.reduced);
+            assert_eq!(trace.survivors.len() + trace.ignored.len() + 2 * trace.reactions.len(), polymer.len());
+        }
+
+        // "dabAcCaCBAcCcaDA" with 'c' removed is "dabAaBAaDA"
+        let trace = reduce_traced(&polymer, Some('c'), &CasePair);
+        assert_eq!(pairs(&trace.reactions), vec![(3, 6, 0), (2, 8, 1), (1, 9, 2)]);
+        assert_eq!(trace.ignored, vec![4, 5, 7, 10, 11, 12]);
+    }
+}
@@ -0,0 +1,190 @@
+use std::io::{Write, Error, ErrorKind};
+use crate::Point;
+use crate::map::PointMap;
+
+pub type Rgb = [u8; 3];
+
+const TIED : Rgb = [0, 0, 0];
+const MARKER : Rgb = [255, 255, 255];
+// About 200MB of pixels
+const MAX_PIXELS : u128 = 1 << 26;
+
+pub struct Image {
+    pub width : usize,
+    pub height : usize,
+    pub pixels : Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![TIED; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        self.pixels[y * self.width + x] = colour;
+    }
+
+    // Binary PPM, which most image viewers (and anything netpbm) will open
+    pub fn write_ppm<W: Write>(&self, w: &mut W) -> Result<(), Error> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let bytes : Vec<u8> = self.pixels.iter().flat_map(|p| p.iter().cloned()).collect();
+        w.write_all(&bytes)
+    }
+}
+
+// Six shades, each walking the hue round by the golden ratio, so neighbouring indices never
+// look alike and a thousand or so points all get different colours.
+pub fn colour(index: usize) -> Rgb {
+    let hue = ((index / 6) as f64 * 0.618_033_988_749_895).fract() * 6.0;
+    let value = [1.0, 0.8, 0.6][index % 3];
+    let saturation = [0.8, 0.5][(index / 3) % 2];
+
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as usize {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    [((r + m) * 255.0) as u8, ((g + m) * 255.0) as u8, ((b + m) * 255.0) as u8]
+}
+
+fn mix(a: Rgb, b: Rgb) -> Rgb {
+    [((a[0] as u16 + b[0] as u16) / 2) as u8, ((a[1] as u16 + b[1] as u16) / 2) as u8, ((a[2] as u16 + b[2] as u16) / 2) as u8]
+}
+
+// One pixel per location over the points' bounding box plus a margin. Each point's region gets
+// its own colour, greyed out if it's infinite; tied locations are black and the points
+// themselves white. Given a threshold, the locations whose total distance is under it are
+// washed towards white, and the margin grows so the whole of that region fits (unless there
+// are no points, when it has no edge to fit). The box stops at the edges of the i64 range, and
+// anything over MAX_PIXELS is an error rather than an attempt at a huge allocation.
+pub fn render(map: &PointMap, safe: Option<u64>) -> Result<Image, Error> {
+    let points = map.points();
+    let mut margin = 2;
+    if let Some(target) = safe.filter(|_| !points.is_empty()) {
+        margin = std::cmp::max(margin, std::cmp::min(target.div_ceil(points.len() as u64), i64::MAX as u64) as i64);
+    }
+    let min_x = points.iter().map(|p| p.x).min().unwrap_or(0).saturating_sub(margin);
+    let max_x = points.iter().map(|p| p.x).max().unwrap_or(0).saturating_add(margin);
+    let min_y = points.iter().map(|p| p.y).min().unwrap_or(0).saturating_sub(margin);
+    let max_y = points.iter().map(|p| p.y).max().unwrap_or(0).saturating_add(margin);
+
+    let width = (max_x as i128 - min_x as i128 + 1) as u128;
+    let height = (max_y as i128 - min_y as i128 + 1) as u128;
+    if width.saturating_mul(height) > MAX_PIXELS {
+        return Err(Error::new(ErrorKind::InvalidInput,
+                              format!("A {} by {} image is too big to render", width, height)));
+    }
+
+    let mut image = Image::new(width as usize, height as usize);
+    for y in min_y..=max_y {
+        for x in min_x..=max_x {
+            let location = Point::new(x, y);
+            let mut pixel = match map.owner(&location) {
+                Some(i) if map.is_infinite(i) => mix(colour(i), [96, 96, 96]),
+                Some(i) => colour(i),
+                None => TIED,
+            };
+            if safe.is_some_and(|target| map.total_distance(&location) < target as f64) {
+                pixel = mix(pixel, MARKER);
+            }
+            image.set((x - min_x) as usize, (y - min_y) as usize, pixel);
+        }
+    }
+
+    for p in points.iter() {
+        image.set((p.x - min_x) as usize, (p.y - min_y) as usize, MARKER);
+    }
+
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::Point;
+    use crate::map::PointMap;
+    use crate::metric::Metric;
+    use crate::image::{colour, render};
+    use crate::tests::example;
+
+    fn map() -> PointMap {
+        PointMap::new(example(), Metric::Manhattan)
+    }
+
+    #[test]
+    fn colours_differ() {
+        let colours : HashSet<[u8; 3]> = (0..1000).map(colour).collect();
+        assert_eq!(colours.len(), 1000);
+    }
+
+    #[test]
+    fn ppm() {
+        let image = render(&map(), None).unwrap();
+        assert_eq!((image.width, image.height), (12, 13));
+        // (5, 0) is as close to A as to E; the image starts at (-1, -1)
+        assert_eq!(image.get(6, 1), [0, 0, 0]);
+        assert_eq!(image.get(2, 2), [255, 255, 255]);
+        assert_eq!(image.get(5, 5), colour(3));
+
+        let mut out : Vec<u8> = vec![];
+        image.write_ppm(&mut out).unwrap();
+        assert!(out.starts_with(b"P6\n12 13\n255\n"));
+        assert_eq!(out.len(), 13 + 12 * 13 * 3);
+    }
+
+    #[test]
+    fn safe_region() {
+        // The puzzle's example region under 32 has 16 locations, which should be the only
+        // pixels that change apart from D and E, which stay white. The margin grows from 2
+        // to 32 / 6 rounded up.
+        let plain = render(&map(), None).unwrap();
+        let marked = render(&map(), Some(32)).unwrap();
+        assert_eq!((marked.width, marked.height), (20, 21));
+        let mut changed = 0;
+        for y in 0..plain.height {
+            for x in 0..plain.width {
+                if plain.get(x, y) != marked.get(x + 4, y + 4) {
+                    changed += 1;
+                }
+            }
+        }
+        assert_eq!(changed, 14);
+    }
+
+    #[test]
+    fn no_points() {
+        // Everywhere is within any threshold of nothing, so it's all washed out
+        let image = render(&PointMap::new(vec![], Metric::Manhattan), Some(10)).unwrap();
+        assert_eq!((image.width, image.height), (5, 5));
+        assert!(image.pixels.iter().all(|p| *p == [127, 127, 127]));
+    }
+
+    #[test]
+    fn far_out() {
+        // Right up against the ends of the i64 range is fine as long as the image is small
+        let corner = PointMap::new(vec![Point::new(i64::MIN, i64::MAX)], Metric::Manhattan);
+        let image = render(&corner, None).unwrap();
+        assert_eq!((image.width, image.height), (3, 3));
+        assert_eq!(image.get(0, 2), [255, 255, 255]);
+
+        // but a huge one is refused rather than allocated
+        let apart = PointMap::new(vec![Point::new(0, 0), Point::new(1 << 40, 1 << 40)], Metric::Manhattan);
+        assert!(render(&apart, None).is_err());
+        let spread = PointMap::new(vec![Point::new(i64::MIN, i64::MIN), Point::new(i64::MAX, i64::MAX)], Metric::Chebyshev);
+        assert!(render(&spread, Some(10)).is_err());
+    }
+}
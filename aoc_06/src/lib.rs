@@ -0,0 +1,102 @@
+use std::io::{Read, BufReader, BufRead, Error, ErrorKind};
+
+pub mod image;
+pub mod map;
+pub mod metric;
+pub mod regions;
+pub mod safe;
+
+// Display name for a point index: A..Z, then AA, AB, ... like spreadsheet columns
+pub fn label(index: usize) -> String {
+    let mut label = vec![];
+    let mut n = index + 1;
+    while n > 0 {
+        n -= 1;
+        label.push((b'A' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    label.iter().rev().collect()
+}
+
+// A single character per point for drawing grids. There are only 62 to go round, so with more
+// points than that they repeat; use `label` when it needs to be unambiguous.
+pub fn label_char(index: usize) -> char {
+    const SYMBOLS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    SYMBOLS[index % SYMBOLS.len()] as char
+}
+
+pub fn read<R: Read>(io: R) -> Vec<String> {
+    let br = BufReader::new(io);
+    br.lines().map(|l| l.unwrap()).collect()
+}
+
+// Any quadrant is fine. Ranks and areas are exact across the whole i64 range, except that
+// Euclidean distances square their differences, so those want coordinates within 2^62 for
+// ranks and 2^61 for areas.
+#[derive(Debug)]
+#[derive(Copy, Clone)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub fn new(x: i64,  y: i64) -> Self {
+        Self {
+            x, y,
+        }
+    }
+
+    pub fn from_string(s : &str) -> Result<Self, Error> {
+        let nums : Vec<&str> = s.split(',').map(|n| n.trim()).collect();
+        if nums.len() != 2 {
+            return Err(Error::new(ErrorKind::InvalidInput, "Invalid input"));
+        }
+
+        let x = nums[0].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        let y = nums[1].parse().map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+
+        Ok(
+            Self {
+                x, y,
+            }
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{label, label_char, Point};
+
+    // The puzzle's example, A to F
+    pub const EXAMPLE : [(i64, i64); 6] = [(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)];
+
+    pub fn points(coords: &[(i64, i64)]) -> Vec<Point> {
+        coords.iter().map(|(x, y)| Point::new(*x, *y)).collect()
+    }
+
+    pub fn example() -> Vec<Point> {
+        points(&EXAMPLE)
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(label(0), "A");
+        assert_eq!(label(25), "Z");
+        assert_eq!(label(26), "AA");
+        assert_eq!(label(701), "ZZ");
+        assert_eq!(label(702), "AAA");
+        assert_eq!(label_char(26), 'a');
+        assert_eq!(label_char(62), 'A');
+    }
+
+    #[test]
+    fn signed_points() {
+        let p = Point::from_string("-3, 7").unwrap();
+        assert_eq!((p.x, p.y), (-3, 7));
+        let p = Point::from_string("9223372036854775807, -9223372036854775808").unwrap();
+        assert_eq!((p.x, p.y), (i64::MAX, i64::MIN));
+        assert!(Point::from_string("1, 2, 3").is_err());
+        assert!(Point::from_string("1, x").is_err());
+    }
+}
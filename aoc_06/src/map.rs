@@ -0,0 +1,118 @@
+use crate::Point;
+use crate::metric::Metric;
+use crate::regions;
+use crate::safe;
+
+// Everything there is to ask about one set of points under one metric. Points are referred
+// to by their index in the list it was built from.
+#[derive(Debug, Clone)]
+pub struct PointMap {
+    points : Vec<Point>,
+    metric : Metric,
+    infinite : Vec<bool>,
+}
+
+impl PointMap {
+    pub fn new(points: Vec<Point>, metric: Metric) -> Self {
+        let infinite = regions::infinite(&points, metric);
+        Self {
+            points,
+            metric,
+            infinite,
+        }
+    }
+
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    // The point strictly closest to q, None if there's a tie
+    pub fn owner(&self, q: &Point) -> Option<usize> {
+        regions::owner(&self.points, self.metric, q.x, q.y)
+    }
+
+    pub fn is_infinite(&self, i: usize) -> bool {
+        self.infinite[i]
+    }
+
+    // Indices of the points owning infinitely many locations
+    pub fn infinite(&self) -> Vec<usize> {
+        (0..self.len()).filter(|i| self.infinite[*i]).collect()
+    }
+
+    // How many locations point i owns, None if that's infinitely many
+    pub fn area(&self, i: usize) -> Option<usize> {
+        if self.infinite[i] {
+            None
+        } else {
+            Some(regions::area(&self.points, self.metric, i))
+        }
+    }
+
+    pub fn finite_areas(&self) -> Vec<Option<usize>> {
+        (0..self.len()).map(|i| self.area(i)).collect()
+    }
+
+    // (index, area) of the biggest finite region, the later index on a tie
+    pub fn largest_finite(&self) -> Option<(usize, usize)> {
+        self.finite_areas().iter().enumerate()
+            .filter_map(|(i, a)| a.map(|a| (i, a)))
+            .max_by_key(|(i, a)| (*a, *i))
+    }
+
+    pub fn total_distance(&self, q: &Point) -> f64 {
+        regions::total_distance(&self.points, self.metric, q.x, q.y)
+    }
+
+    // Number of locations whose total distance is under target, None if that's unbounded
+    pub fn safe_size(&self, target: u64) -> Option<usize> {
+        safe::region_size(&self.points, self.metric, target)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Point;
+    use crate::metric::Metric;
+    use crate::map::PointMap;
+    use crate::tests::example;
+
+    fn map(metric: Metric) -> PointMap {
+        PointMap::new(example(), metric)
+    }
+
+    #[test]
+    fn queries() {
+        let map = map(Metric::Manhattan);
+        assert_eq!(map.owner(&Point::new(0, 0)), Some(0));
+        assert_eq!(map.owner(&Point::new(5, 0)), None);
+        assert_eq!(map.owner(&Point::new(-100, 100)), Some(1));
+        assert_eq!(map.infinite(), vec![0, 1, 2, 5]);
+        assert_eq!(map.area(3), Some(9));
+        assert_eq!(map.area(5), None);
+        assert_eq!(map.largest_finite(), Some((4, 17)));
+        assert_eq!(map.total_distance(&Point::new(4, 3)), 30.0);
+        assert_eq!(map.safe_size(32), Some(16));
+        assert_eq!(map.safe_size(10), Some(0));
+    }
+
+    #[test]
+    fn other_metrics() {
+        let map = map(Metric::Chebyshev);
+        assert_eq!(map.finite_areas().len(), 6);
+        assert_eq!(map.owner(&Point::new(3, 4)), Some(3));
+        assert!(map.safe_size(40).unwrap() > 0);
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+use crate::Point;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Metric {
+    Manhattan,
+    Chebyshev,
+    Euclidean,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 3] = [Metric::Manhattan, Metric::Chebyshev, Metric::Euclidean];
+
+    // Orders distances exactly, so ties can be detected. Only comparable with other ranks from
+    // the same metric (Euclidean ranks are squared distances, so need dx and dy under 2^63).
+    pub fn rank(&self, dx: u64, dy: u64) -> u128 {
+        let (dx, dy) = (dx as u128, dy as u128);
+        match self {
+            Metric::Manhattan => dx + dy,
+            Metric::Chebyshev => std::cmp::max(dx, dy),
+            Metric::Euclidean => dx * dx + dy * dy,
+        }
+    }
+
+    pub fn distance(&self, dx: u64, dy: u64) -> f64 {
+        match self {
+            Metric::Euclidean => (self.rank(dx, dy) as f64).sqrt(),
+            _ => self.rank(dx, dy) as f64,
+        }
+    }
+
+    pub fn rank_between(&self, a: &Point, b: &Point) -> u128 {
+        self.rank(diff(a.x, b.x), diff(a.y, b.y))
+    }
+}
+
+// |a - b|, which always fits in a u64 even though it might not in an i64
+pub fn diff(a: i64, b: i64) -> u64 {
+    (a as i128 - b as i128).unsigned_abs() as u64
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Metric::Manhattan => write!(f, "Manhattan"),
+            Metric::Chebyshev => write!(f, "Chebyshev"),
+            Metric::Euclidean => write!(f, "Euclidean"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Point;
+    use crate::metric::Metric;
+
+    #[test]
+    fn distances() {
+        let a = Point::new(1, 1);
+        let b = Point::new(4, 5);
+        assert_eq!(Metric::Manhattan.rank_between(&a, &b), 7);
+        assert_eq!(Metric::Chebyshev.rank_between(&a, &b), 4);
+        assert_eq!(Metric::Euclidean.rank_between(&b, &a), 25);
+        assert_eq!(Metric::Euclidean.distance(3, 4), 5.0);
+        assert_eq!(Metric::Chebyshev.distance(3, 4), 4.0);
+    }
+
+    #[test]
+    fn far_apart() {
+        let a = Point::new(i64::MIN, i64::MIN);
+        let b = Point::new(i64::MAX, i64::MAX);
+        assert_eq!(Metric::Chebyshev.rank_between(&a, &b), u64::MAX as u128);
+        assert_eq!(Metric::Manhattan.rank_between(&a, &b), 2 * u64::MAX as u128);
+        let c = Point::new(-(1 << 62), 1 << 62);
+        assert_eq!(Metric::Euclidean.rank_between(&c, &Point::new(1 << 62, -(1 << 62))), 1 << 127);
+    }
+}
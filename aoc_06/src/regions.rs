@@ -0,0 +1,426 @@
+use std::cmp::{max, min, Ordering};
+use crate::Point;
+use crate::metric::{diff, Metric};
+
+fn coords(p: &Point) -> (i64, i64) {
+    (p.x, p.y)
+}
+
+// Room for sums and differences of coordinates without worrying about overflow
+fn wide(p: &Point) -> (i128, i128) {
+    (p.x as i128, p.y as i128)
+}
+
+fn rank_at(metric: Metric, p: &Point, x: i64, y: i64) -> u128 {
+    metric.rank(diff(p.x, x), diff(p.y, y))
+}
+
+// Index of the point strictly closest to (x, y), None if there's a tie
+pub fn owner(points: &[Point], metric: Metric, x: i64, y: i64) -> Option<usize> {
+    let mut best : Option<usize> = None;
+    let mut best_rank = u128::MAX;
+    let mut tied = false;
+    for (i, p) in points.iter().enumerate() {
+        let rank = rank_at(metric, p, x, y);
+        if rank < best_rank {
+            best = Some(i);
+            best_rank = rank;
+            tied = false;
+        } else if rank == best_rank {
+            tied = true;
+        }
+    }
+
+    if tied {
+        None
+    } else {
+        best
+    }
+}
+
+// Sum of the distances from (x, y) to every point
+pub fn total_distance(points: &[Point], metric: Metric, x: i64, y: i64) -> f64 {
+    points.iter().map(|p| metric.distance(diff(p.x, x), diff(p.y, y))).sum()
+}
+
+// Whether each point owns infinitely many locations
+pub fn infinite(points: &[Point], metric: Metric) -> Vec<bool> {
+    match metric {
+        Metric::Manhattan => edge_infinite(&points.iter().map(wide).collect::<Vec<_>>()),
+        // Chebyshev is Manhattan on the lattice turned 45 degrees (and scaled, which doesn't
+        // change who is closest)
+        Metric::Chebyshev => {
+            let turned : Vec<(i128, i128)> = points.iter().map(wide).map(|(x, y)| (x + y, x - y)).collect();
+            edge_infinite(&turned)
+        },
+        Metric::Euclidean => hull_infinite(&points.iter().map(wide).collect::<Vec<_>>()),
+    }
+}
+
+// Under Manhattan distance, everything past the right-hand edge of the points' bounding box is
+// owned by whoever owns the spot on the edge in the same row: walking right adds the same to
+// every distance. That owner is whoever minimises |y - p.y| - p.x, so a point owns an infinite
+// region iff it is the unique minimum of that (or the same thing for the other three edges)
+// for some row.
+fn edge_infinite(coords: &[(i128, i128)]) -> Vec<bool> {
+    // (position along the edge, offset) of each point for each edge
+    type Edge = fn(&(i128, i128)) -> (i128, i128);
+    let edges : [Edge; 4] = [
+        |c| (c.1, -c.0),
+        |c| (c.1, c.0),
+        |c| (c.0, -c.1),
+        |c| (c.0, c.1),
+    ];
+    let shapes : Vec<Vec<(i128, i128)>> = edges.iter().map(|edge| coords.iter().map(edge).collect()).collect();
+
+    (0..coords.len()).map(|i| shapes.iter().any(|vs| wins_somewhere(vs, i))).collect()
+}
+
+// Each entry is a V shape |s - a| + c. Is vs[i] strictly below all the others at some integer s?
+fn wins_somewhere(vs: &[(i128, i128)], i: usize) -> bool {
+    winning(vs, i).is_some()
+}
+
+// The integers s (first to last, inclusive) where vs[i] is strictly below all the others,
+// None if there aren't any
+fn winning(vs: &[(i128, i128)], i: usize) -> Option<(i128, i128)> {
+    let (a_p, c_p) = vs[i];
+    let mut lo = i128::MIN;
+    let mut hi = i128::MAX;
+
+    for (j, (a_q, c_q)) in vs.iter().enumerate() {
+        if j == i {
+            continue;
+        }
+        // Need |s - a_q| - |s - a_p| > k. The left side is a clamped slope running
+        // between -d and d, so this is a half-line of s (or everything, or nothing).
+        let k = c_p - c_q;
+        let d = (a_q - a_p).abs();
+        if k >= d {
+            return None;
+        }
+        if k < -d {
+            continue;
+        }
+        if *a_q > a_p {
+            hi = min(hi, (a_q + a_p - k - 1).div_euclid(2));
+        } else {
+            lo = max(lo, -(-(a_q + a_p + k + 1)).div_euclid(2));
+        }
+    }
+
+    if lo <= hi { Some((lo, hi)) } else { None }
+}
+
+fn cross(o: &(i128, i128), a: &(i128, i128), b: &(i128, i128)) -> i128 {
+    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+}
+
+// Under Euclidean distance a point's region is unbounded iff it sits on the boundary of the
+// convex hull, corners and the middle of edges alike. Repeated points own nothing.
+fn hull_infinite(coords: &[(i128, i128)]) -> Vec<bool> {
+    let mut sorted : Vec<(i128, i128)> = coords.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    // Andrew's monotone chain, dropping collinear points
+    let mut hull : Vec<(i128, i128)> = vec![];
+    for pass in 0..2 {
+        let start = hull.len();
+        let points : Vec<&(i128, i128)> = if pass == 0 { sorted.iter().collect() } else { sorted.iter().rev().collect() };
+        for p in points {
+            while hull.len() >= start + 2 && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], p) <= 0 {
+                hull.pop();
+            }
+            hull.push(*p);
+        }
+        hull.pop();
+    }
+
+    let on_boundary = |p: &(i128, i128)| {
+        if hull.len() < 3 {
+            // Everything is on one line
+            return true;
+        }
+        (0..hull.len()).any(|i| {
+            let a = &hull[i];
+            let b = &hull[(i + 1) % hull.len()];
+            cross(a, b, p) == 0
+                && min(a.0, b.0) <= p.0 && p.0 <= max(a.0, b.0)
+                && min(a.1, b.1) <= p.1 && p.1 <= max(a.1, b.1)
+        })
+    };
+
+    coords.iter().map(|p| coords.iter().filter(|q| *q == p).count() == 1 && on_boundary(p)).collect()
+}
+
+// Number of locations each point owns, None for the infinite ones
+pub fn finite_areas(points: &[Point], metric: Metric) -> Vec<Option<usize>> {
+    let infinite = infinite(points, metric);
+    (0..points.len()).map(|i| if infinite[i] { None } else { Some(area(points, metric, i)) }).collect()
+}
+
+// Number of locations point i owns. Only meaningful if its region is finite, otherwise this
+// never finishes.
+pub fn area(points: &[Point], metric: Metric, i: usize) -> usize {
+    match metric {
+        Metric::Manhattan | Metric::Chebyshev => lines(points, metric, i),
+        Metric::Euclidean => rows(points, i),
+    }
+}
+
+// Under Manhattan distance a point's share of a row is the stretch where its V shape is
+// lowest, which `winning` finds directly. The region is connected (every location on a shortest
+// path from the point to something it owns is owned by it too), so rows are walked out from the
+// point's own until one comes up empty.
+//
+// Chebyshev is Manhattan on the lattice turned 45 degrees, as for `infinite`, except that only
+// every other spot along a turned row is a real location.
+fn lines(points: &[Point], metric: Metric, i: usize) -> usize {
+    let turn = |p: &Point| {
+        let (x, y) = wide(p);
+        match metric {
+            Metric::Chebyshev => (x - y, x + y),
+            _ => (x, y),
+        }
+    };
+    let coords : Vec<(i128, i128)> = points.iter().map(turn).collect();
+    let (_, p_row) = coords[i];
+
+    // (along, row) of every point, as V shapes along the given row
+    let line = |row: i128| {
+        let vs : Vec<(i128, i128)> = coords.iter().map(|(a, b)| (*a, (row - b).abs())).collect();
+        winning(&vs, i).map(|(first, last)| match metric {
+            // Real locations have the same parity in both turned coordinates
+            Metric::Chebyshev => ((last - row).div_euclid(2) - (first - 1 - row).div_euclid(2)) as usize,
+            _ => (last - first + 1) as usize,
+        })
+    };
+
+    let mut total = match line(p_row) {
+        Some(count) => count,
+        None => return 0,
+    };
+    for dir in [1, -1].iter() {
+        let mut row = p_row + dir;
+        while let Some(count) = line(row) {
+            total += count;
+            row += dir;
+        }
+    }
+    total
+}
+
+// Euclidean regions are convex, but the locations in one needn't be connected. Instead, for
+// each row, being closer to p than to q is a half-line of x, so the row's share is an interval.
+// Rows are walked out from p's until the (real) interval is empty.
+fn rows(points: &[Point], i: usize) -> usize {
+    let (px, py) = coords(&points[i]);
+    if points.iter().filter(|q| coords(q) == (px, py)).count() > 1 {
+        return 0;
+    }
+
+    let mut total = row(points, i, py).unwrap_or(0);
+    for dir in [1, -1].iter() {
+        let mut y = py + dir;
+        while let Some(count) = row(points, i, y) {
+            total += count;
+            y += dir;
+        }
+    }
+    total
+}
+
+// Compares n1 / d1 with n2 / d2 (positive denominators) without multiplying anything, which
+// could overflow. Same whole parts means comparing the leftovers, i.e. flipping both over.
+fn cmp_fraction(n1: i128, d1: i128, n2: i128, d2: i128) -> Ordering {
+    let (q1, r1) = (n1.div_euclid(d1), n1.rem_euclid(d1));
+    let (q2, r2) = (n2.div_euclid(d2), n2.rem_euclid(d2));
+    if q1 != q2 {
+        return q1.cmp(&q2);
+    }
+    match (r1, r2) {
+        (0, 0) => Ordering::Equal,
+        (0, _) => Ordering::Less,
+        (_, 0) => Ordering::Greater,
+        _ => cmp_fraction(d2, r2, d1, r1),
+    }
+}
+
+// Number of integer x in row y owned by point i, None if the row misses the region entirely
+fn row(points: &[Point], i: usize, y: i64) -> Option<usize> {
+    let (px, py) = wide(&points[i]);
+    let y = y as i128;
+
+    // Open interval (lo, hi) as fractions with positive denominators
+    let mut lo : Option<(i128, i128)> = None;
+    let mut hi : Option<(i128, i128)> = None;
+
+    for (j, q) in points.iter().enumerate() {
+        if j == i {
+            continue;
+        }
+        let (qx, qy) = wide(q);
+
+        // |(x, y) - p|^2 < |(x, y) - q|^2  <=>  a * x < r. Factored so that coordinates
+        // within 2^61 keep r inside an i128.
+        let a = 2 * (qx - px);
+        let r = (qx - px) * (qx + px) + (qy - py) * (qy + py - 2 * y);
+        if a == 0 {
+            if r <= 0 {
+                return None;
+            }
+        } else if a > 0 {
+            if hi.is_none_or(|(n, d)| cmp_fraction(r, a, n, d) == Ordering::Less) {
+                hi = Some((r, a));
+            }
+        } else if lo.is_none_or(|(n, d)| cmp_fraction(-r, -a, n, d) == Ordering::Greater) {
+            lo = Some((-r, -a));
+        }
+    }
+
+    // Regions that get here are finite, so both ends are bounded
+    let (lo_n, lo_d) = lo?;
+    let (hi_n, hi_d) = hi?;
+    if cmp_fraction(lo_n, lo_d, hi_n, hi_d) != Ordering::Less {
+        return None;
+    }
+
+    let first = lo_n.div_euclid(lo_d) + 1;
+    let last = -(-hi_n).div_euclid(hi_d) - 1;
+    Some(max(last - first + 1, 0) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Point;
+    use crate::metric::Metric;
+    use crate::regions::{area, finite_areas, infinite, owner};
+    use crate::tests::{example, points};
+
+    // Counts ownership over a big square the slow way
+    fn brute_areas(points: &[Point], metric: Metric, reach: i64) -> Vec<usize> {
+        let mut areas = vec![0; points.len()];
+        for x in -reach..=reach {
+            for y in -reach..=reach {
+                if let Some(i) = owner(points, metric, x, y) {
+                    areas[i] += 1;
+                }
+            }
+        }
+        areas
+    }
+
+    fn check_against_brute_force(points: &[Point]) {
+        for metric in Metric::ALL.iter() {
+            let areas = finite_areas(points, *metric);
+            let near = brute_areas(points, *metric, 60);
+            let far = brute_areas(points, *metric, 90);
+            for i in 0..points.len() {
+                match areas[i] {
+                    Some(area) => {
+                        assert_eq!(area, near[i], "{} point {}", metric, i);
+                        assert_eq!(area, far[i], "{} point {}", metric, i);
+                    },
+                    None => assert!(far[i] > near[i], "{} point {}", metric, i),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn example_manhattan() {
+        assert_eq!(finite_areas(&example(), Metric::Manhattan),
+                   vec![None, None, None, Some(9), Some(17), None]);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        check_against_brute_force(&example());
+        check_against_brute_force(&points(&[(10, 10), (12, 10), (11, 11), (11, 9), (11, 10), (0, 0), (22, 1), (0, 21), (23, 22)]));
+        check_against_brute_force(&points(&[(0, 0), (4, 4), (8, 8), (2, 6)]));
+        check_against_brute_force(&points(&[(5, 5), (5, 5), (9, 1), (1, 9), (5, 7)]));
+    }
+
+    #[test]
+    fn many_points() {
+        // 900 points four apart, well past anything a char or a [_; 256] could index
+        let mut coords = vec![];
+        for x in 0..30 {
+            for y in 0..30 {
+                coords.push((x * 4, y * 4));
+            }
+        }
+        let points = points(&coords);
+        let areas = finite_areas(&points, Metric::Manhattan);
+        let infinite : Vec<usize> = (0..points.len()).filter(|i| areas[*i].is_none()).collect();
+        assert_eq!(infinite.len(), 4 * 29);
+        // Interior points keep the 3x3 block around them, everything two away is tied
+        assert_eq!(areas[15 * 30 + 15], Some(9));
+        assert_eq!(areas[899 - 31], Some(9));
+    }
+
+    #[test]
+    fn widely_spread() {
+        // Areas cost per row rather than per location, so spreading the example a thousand
+        // times wider is no trouble. The numbers are from counting locations one by one.
+        for (k, manhattan, chebyshev) in [(37, [13977, 23597], [17270, 20998]),
+                                          (1000, [10244501, 17241501], [12744001, 15493501])].iter() {
+            let spread : Vec<Point> = example().iter().map(|p| Point::new(p.x * k - 3, p.y * k + 5)).collect();
+            assert_eq!(finite_areas(&spread, Metric::Manhattan), vec![None, None, None, Some(manhattan[0]), Some(manhattan[1]), None]);
+            assert_eq!(finite_areas(&spread, Metric::Chebyshev), vec![None, None, None, Some(chebyshev[0]), Some(chebyshev[1]), None]);
+        }
+    }
+
+    #[test]
+    fn any_quadrant() {
+        // Moving everything doesn't change anyone's area, however far it goes
+        let example : Vec<Point> = example();
+        for (dx, dy) in [(-20, -20), (-1_000_000_000_000, 7), ((1 << 61) - 8, -(1 << 61))].iter() {
+            let moved : Vec<Point> = example.iter().map(|p| Point::new(p.x + dx, p.y + dy)).collect();
+            for metric in Metric::ALL.iter() {
+                assert_eq!(finite_areas(&moved, *metric), finite_areas(&example, *metric), "{} ({}, {})", metric, dx, dy);
+            }
+        }
+
+        // Manhattan and Chebyshev cope with the full range
+        let far = points(&[(i64::MIN, i64::MIN), (i64::MAX, i64::MAX), (i64::MIN, i64::MAX), (i64::MAX, i64::MIN), (0, 0)]);
+        assert_eq!(infinite(&far, Metric::Manhattan), vec![true, true, true, true, false]);
+        assert_eq!(infinite(&far, Metric::Chebyshev), vec![true, true, true, true, false]);
+        assert_eq!(owner(&far, Metric::Manhattan, i64::MAX, i64::MAX - 5), Some(1));
+    }
+
+    #[test]
+    fn edge_is_not_infinite() {
+        // (4, 1) touches the bottom of the box but is fenced in under Euclidean distance
+        let points = points(&[(0, 0), (8, 0), (4, 1), (4, 9)]);
+        assert_eq!(infinite(&points, Metric::Euclidean), vec![true, true, false, true]);
+    }
+
+    #[test]
+    fn euclidean_at_the_bound() {
+        // Small plus shapes in the middle and in a corner of a box as big as Euclidean areas
+        // allow. The far corners make for huge bounds on each row that must still compare
+        // exactly with the small ones.
+        let b : i64 = 1 << 61;
+        let mut coords = vec![(-b, -b), (b, -b), (-b, b), (b, b)];
+        for (cx, cy) in [(0, 0), (b - 4, b - 4), (4 - b, 0)].iter() {
+            coords.extend_from_slice(&[(*cx, *cy), (cx + 4, *cy), (cx - 4, *cy), (*cx, cy + 4), (*cx, cy - 4)]);
+        }
+        let points = points(&coords);
+
+        for centre in [4, 9, 14].iter() {
+            let p = points[*centre];
+            let mut brute = 0;
+            for x in (p.x - 4)..=(p.x + 4) {
+                for y in (p.y - 4)..=(p.y + 4) {
+                    if owner(&points, Metric::Euclidean, x, y) == Some(*centre) {
+                        brute += 1;
+                    }
+                }
+            }
+            assert_eq!(area(&points, Metric::Euclidean, *centre), 9, "point {}", centre);
+            assert_eq!(brute, 9, "point {}", centre);
+        }
+    }
+}
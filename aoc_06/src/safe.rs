@@ -0,0 +1,155 @@
+use crate::Point;
+use crate::metric::{diff, Metric};
+use crate::regions;
+
+// Number of locations whose total distance to all the points is under target. None means
+// there's no bound on it, which only happens with no points at all.
+pub fn region_size(points: &[Point], metric: Metric, target: u64) -> Option<usize> {
+    if points.is_empty() {
+        return if target == 0 { Some(0) } else { None };
+    }
+
+    Some(match metric {
+        Metric::Manhattan => manhattan(points, target),
+        _ => scan(points, metric, target),
+    })
+}
+
+// The Manhattan total splits into X(x) + Y(y), each the sum of distances along one axis. So
+// work out every X and Y under the target, then count the pairs that still fit together.
+fn manhattan(points: &[Point], target: u64) -> usize {
+    let mut xs = line_totals(points.iter().map(|p| p.x).collect(), target as i128);
+    let mut ys = line_totals(points.iter().map(|p| p.y).collect(), target as i128);
+    xs.sort_unstable();
+    ys.sort_unstable();
+
+    // As x's share grows, fewer of the y's fit alongside it
+    let mut count = 0;
+    let mut fits = ys.len();
+    for x in xs.iter() {
+        while fits > 0 && x + ys[fits - 1] >= target as i128 {
+            fits -= 1;
+        }
+        count += fits;
+    }
+    count
+}
+
+// Sum of |x - c| for every integer x that keeps it under target
+fn line_totals(mut cs: Vec<i64>, target: i128) -> Vec<i128> {
+    cs.sort_unstable();
+    let n = cs.len() as i128;
+
+    // Any median minimises the sum. Moving right from x adds one for everything at or left
+    // of x and takes one for everything right of it, and the other way round going left.
+    let median = cs[(cs.len() - 1) / 2];
+    let lowest : i128 = cs.iter().map(|c| diff(*c, median) as i128).sum();
+    let mut totals = vec![];
+    if lowest >= target {
+        return totals;
+    }
+    totals.push(lowest);
+
+    let mut total = lowest;
+    let mut at_or_left = cs.iter().filter(|c| **c <= median).count();
+    let mut x = median as i128;
+    loop {
+        total += 2 * at_or_left as i128 - n;
+        x += 1;
+        if total >= target {
+            break;
+        }
+        totals.push(total);
+        while at_or_left < cs.len() && cs[at_or_left] as i128 <= x {
+            at_or_left += 1;
+        }
+    }
+
+    let mut total = lowest;
+    let mut left_of = cs.iter().filter(|c| **c < median).count();
+    let mut x = median as i128;
+    loop {
+        total += n - 2 * left_of as i128;
+        x -= 1;
+        if total >= target {
+            break;
+        }
+        totals.push(total);
+        while left_of > 0 && cs[left_of - 1] as i128 >= x {
+            left_of -= 1;
+        }
+    }
+
+    totals
+}
+
+// Works for any metric: a point whose distance to the bounding box is at least target / n is
+// at least that far from every input point, so only the box grown by that much needs a look.
+// The box stops at the edges of the i64 range rather than wrapping.
+fn scan(points: &[Point], metric: Metric, target: u64) -> usize {
+    let min_x = points.iter().map(|p| p.x).min().unwrap();
+    let max_x = points.iter().map(|p| p.x).max().unwrap();
+    let min_y = points.iter().map(|p| p.y).min().unwrap();
+    let max_y = points.iter().map(|p| p.y).max().unwrap();
+    let margin = std::cmp::min(target.div_ceil(points.len() as u64), i64::MAX as u64) as i64;
+
+    let mut accepted = 0;
+    for x in min_x.saturating_sub(margin)..=max_x.saturating_add(margin) {
+        for y in min_y.saturating_sub(margin)..=max_y.saturating_add(margin) {
+            if regions::total_distance(points, metric, x, y) < target as f64 {
+                accepted += 1;
+            }
+        }
+    }
+    accepted
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metric::Metric;
+    use crate::safe::{region_size, scan};
+    use crate::tests::{example, points};
+
+    #[test]
+    fn example_region() {
+        assert_eq!(region_size(&example(), Metric::Manhattan, 32), Some(16));
+    }
+
+    #[test]
+    fn odd_sized() {
+        let odd = points(&[(0, 0), (2, 0), (1, 5), (-4, 3), (7, -2)]);
+        for target in [0, 1, 20, 25, 33, 60].iter() {
+            assert_eq!(region_size(&odd, Metric::Manhattan, *target), Some(scan(&odd, Metric::Manhattan, *target)));
+        }
+    }
+
+    #[test]
+    fn beyond_the_points() {
+        // Reaches well past every point in all directions
+        assert_eq!(region_size(&example(), Metric::Manhattan, 500), Some(scan(&example(), Metric::Manhattan, 500)));
+        // A lone point's region is a diamond: 2t^2 - 2t + 1
+        assert_eq!(region_size(&points(&[(-7, 3)]), Metric::Manhattan, 1000), Some(2 * 1000 * 1000 - 2 * 1000 + 1));
+        let twice = points(&[(4, 4), (4, 4)]);
+        assert_eq!(region_size(&twice, Metric::Manhattan, 7), Some(25));
+    }
+
+    #[test]
+    fn at_the_edges() {
+        // The grown box would run off the ends of the i64 range, so only a corner of each
+        // square is left
+        let lone = points(&[(i64::MAX, i64::MIN)]);
+        assert_eq!(scan(&lone, Metric::Chebyshev, 3), 3 * 3);
+        let twice = points(&[(i64::MIN, i64::MAX), (i64::MIN, i64::MAX)]);
+        assert_eq!(scan(&twice, Metric::Chebyshev, 6), 3 * 3);
+    }
+
+    #[test]
+    fn empty() {
+        // The best spots in the example are 28 from everything, and there are six of them
+        assert_eq!(region_size(&example(), Metric::Manhattan, 28), Some(0));
+        assert_eq!(region_size(&example(), Metric::Manhattan, 29), Some(6));
+        assert_eq!(region_size(&example(), Metric::Manhattan, 0), Some(0));
+        assert_eq!(region_size(&[], Metric::Manhattan, 0), Some(0));
+        assert_eq!(region_size(&[], Metric::Manhattan, 10), None);
+    }
+}
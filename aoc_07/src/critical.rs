@@ -0,0 +1,118 @@
+use crate::graph::{Cycle, Graph, StepId};
+use crate::schedule::Durations;
+
+// When a step can run if there are always enough workers. Slack is how long it can slip
+// without holding up the end.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Timing {
+    pub duration : usize,
+    pub earliest_start : usize,
+    pub latest_start : usize,
+}
+
+impl Timing {
+    pub fn earliest_finish(&self) -> usize {
+        self.earliest_start + self.duration
+    }
+
+    pub fn latest_finish(&self) -> usize {
+        self.latest_start + self.duration
+    }
+
+    pub fn slack(&self) -> usize {
+        self.latest_start - self.earliest_start
+    }
+
+    pub fn is_critical(&self) -> bool {
+        self.slack() == 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    // By step id
+    pub timings : Vec<Timing>,
+    // Nothing can finish sooner than this, however many workers there are
+    pub lower_bound : usize,
+    // A longest chain of steps, each waiting on the one before. Where there's a choice it
+    // takes the first step in the usual order.
+    pub critical_path : Vec<StepId>,
+}
+
+pub fn analyse(graph: &Graph, durations: &Durations) -> Result<Analysis, Cycle> {
+    let order = graph.order()?;
+    let lengths = durations.for_graph(graph);
+
+    // Forwards for the earliest starts, then backwards from the end for the latest ones
+    let mut earliest = vec![0; graph.len()];
+    for id in order.iter() {
+        earliest[*id] = graph.prereqs(*id).iter().map(|p| earliest[*p] + lengths[*p]).max().unwrap_or(0);
+    }
+    let lower_bound = graph.steps().map(|id| earliest[id] + lengths[id]).max().unwrap_or(0);
+
+    let mut latest = vec![0; graph.len()];
+    for id in order.iter().rev() {
+        let finish = graph.dependents(*id).iter().map(|d| latest[*d]).min().unwrap_or(lower_bound);
+        latest[*id] = finish - lengths[*id];
+    }
+
+    let timings : Vec<Timing> = graph.steps().map(|id| Timing {
+        duration: lengths[id],
+        earliest_start: earliest[id],
+        latest_start: latest[id],
+    }).collect();
+
+    // Work back from a critical step that finishes last, through prerequisites that finish
+    // exactly when it can start
+    let mut critical_path = vec![];
+    let mut at = order.iter().copied()
+        .find(|id| timings[*id].is_critical() && timings[*id].earliest_finish() == lower_bound);
+    while let Some(id) = at {
+        critical_path.push(id);
+        at = order.iter().copied().find(|p| graph.prereqs(id).contains(p)
+            && timings[*p].is_critical() && timings[*p].earliest_finish() == timings[id].earliest_start);
+    }
+    critical_path.reverse();
+
+    Ok(Analysis {
+        timings,
+        lower_bound,
+        critical_path,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::critical::analyse;
+    use crate::graph::tests::{graph, EXAMPLE};
+    use crate::schedule::{simulate, Durations};
+
+    #[test]
+    fn example() {
+        let graph = graph(&EXAMPLE);
+        let durations = Durations::new(0);
+        let analysis = analyse(&graph, &durations).unwrap();
+        assert_eq!(analysis.lower_bound, 14);
+        assert_eq!(graph.names_of(&analysis.critical_path), vec!["C", "F", "E"]);
+
+        let slack = |name| analysis.timings[graph.id(name).unwrap()].slack();
+        assert_eq!(slack("A"), 1);
+        assert_eq!(slack("B"), 3);
+        assert_eq!(slack("D"), 1);
+        let d = analysis.timings[graph.id("D").unwrap()];
+        assert_eq!((d.earliest_start, d.latest_start, d.latest_finish()), (4, 5, 9));
+
+        // With a worker per step, the simulation gets exactly the lower bound
+        assert_eq!(simulate(&graph, graph.len(), &durations), Ok(analysis.lower_bound));
+        assert!(simulate(&graph, 2, &durations).unwrap() >= analysis.lower_bound);
+    }
+
+    #[test]
+    fn cycle() {
+        let graph = graph(&[
+            "Step a must be finished before step b can begin.",
+            "Step b must be finished before step a can begin.",
+        ]);
+        assert!(analyse(&graph, &Durations::new(1)).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+use std::io::{Write, Error};
+use crate::graph::Graph;
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// Graphviz, for `dot -Tsvg` and friends. Steps and edges come out sorted by name so the file
+// doesn't change between runs.
+pub fn write_dot<W: Write>(graph: &Graph, w: &mut W) -> Result<(), Error> {
+    let mut steps : Vec<&str> = graph.steps().map(|id| graph.name(id)).collect();
+    steps.sort();
+
+    writeln!(w, "digraph steps {{")?;
+    for step in steps.iter() {
+        writeln!(w, "    {};", quote(step))?;
+    }
+    for (from, to) in graph.edges() {
+        writeln!(w, "    {} -> {};", quote(graph.name(from)), quote(graph.name(to)))?;
+    }
+    writeln!(w, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dot::write_dot;
+    use crate::graph::tests::graph;
+
+    #[test]
+    fn dot() {
+        let graph = graph(&[
+            "Step b must be finished before step c can begin.",
+            "Step a\"1 must be finished before step c can begin.",
+        ]);
+        let mut out : Vec<u8> = vec![];
+        write_dot(&graph, &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines : Vec<&str> = text.lines().collect();
+        assert_eq!(lines, vec![
+            "digraph steps {",
+            "    \"a\\\"1\";",
+            "    \"b\";",
+            "    \"c\";",
+            "    \"a\\\"1\" -> \"c\";",
+            "    \"b\" -> \"c\";",
+            "}",
+        ]);
+    }
+}
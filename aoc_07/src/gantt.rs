@@ -0,0 +1,128 @@
+use std::io::{Write, Error};
+use crate::graph::Graph;
+use crate::schedule::Schedule;
+
+// One row per worker, each column `scale` seconds. A step shows as its name followed by
+// dashes for as long as it runs, '.' is idle. Every ten columns get a time along the top.
+pub fn text(graph: &Graph, schedule: &Schedule, scale: usize) -> String {
+    let scale = std::cmp::max(scale, 1);
+    let columns = schedule.makespan.div_ceil(scale);
+    let label = |worker: usize| format!("worker {}", worker);
+    let width = label(schedule.workers.saturating_sub(1)).len() + 2;
+
+    let mut ruler = vec![' '; columns];
+    for column in (0..columns).step_by(10) {
+        for (i, ch) in (column * scale).to_string().chars().enumerate() {
+            if column + i < columns {
+                ruler[column + i] = ch;
+            }
+        }
+    }
+
+    let mut out = String::new();
+    out.push_str(&format!("{:width$}{}\n", "", ruler.iter().collect::<String>().trim_end(), width = width));
+    for worker in 0..schedule.workers {
+        let mut row = vec!['.'; columns];
+        for slot in schedule.slots.iter().filter(|s| s.worker == worker && s.end > s.start) {
+            let first = slot.start / scale;
+            let last = (slot.end - 1) / scale;
+            for cell in row[first..=last].iter_mut() {
+                *cell = '-';
+            }
+            for (cell, ch) in row[first..=last].iter_mut().zip(graph.name(slot.step).chars()) {
+                *cell = ch;
+            }
+        }
+        out.push_str(&format!("{:width$}{}  idle {}\n", label(worker), row.iter().collect::<String>(),
+                              schedule.idle(worker), width = width));
+    }
+    out
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains(',') || s.contains('"') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn write_csv<W: Write>(graph: &Graph, schedule: &Schedule, w: &mut W) -> Result<(), Error> {
+    writeln!(w, "worker,step,start,end")?;
+    for slot in schedule.slots.iter() {
+        writeln!(w, "{},{},{},{}", slot.worker, csv_field(graph.name(slot.step)), slot.start, slot.end)?;
+    }
+    Ok(())
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            ch if (ch as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub fn write_json<W: Write>(graph: &Graph, schedule: &Schedule, w: &mut W) -> Result<(), Error> {
+    let idle : Vec<String> = (0..schedule.workers).map(|worker| schedule.idle(worker).to_string()).collect();
+    writeln!(w, "{{")?;
+    writeln!(w, "  \"workers\": {},", schedule.workers)?;
+    writeln!(w, "  \"makespan\": {},", schedule.makespan)?;
+    writeln!(w, "  \"idle\": [{}],", idle.join(", "))?;
+    writeln!(w, "  \"slots\": [")?;
+    for (i, slot) in schedule.slots.iter().enumerate() {
+        let comma = if i + 1 < schedule.slots.len() { "," } else { "" };
+        writeln!(w, "    {{\"worker\": {}, \"step\": {}, \"start\": {}, \"end\": {}}}{}",
+                 slot.worker, json_string(graph.name(slot.step)), slot.start, slot.end, comma)?;
+    }
+    writeln!(w, "  ]")?;
+    writeln!(w, "}}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::gantt::{text, write_csv, write_json};
+    use crate::graph::tests::{graph, EXAMPLE};
+    use crate::schedule::{run, Durations};
+
+    #[test]
+    fn chart() {
+        let graph = graph(&EXAMPLE);
+        let schedule = run(&graph, 2, &Durations::new(0)).unwrap();
+        let chart = text(&graph, &schedule, 1);
+        let lines : Vec<&str> = chart.lines().collect();
+        assert_eq!(lines, vec![
+            "          0         10",
+            "worker 0  C--AB-D---E----  idle 0",
+            "worker 1  ...F-----......  idle 9",
+        ]);
+
+        let lines : Vec<String> = text(&graph, &schedule, 4).lines().map(|l| l.to_string()).collect();
+        // Steps sharing a column show the one that started last
+        assert_eq!(lines[1], "worker 0  ADE-  idle 0");
+        assert_eq!(lines[2], "worker 1  F--.  idle 9");
+    }
+
+    #[test]
+    fn exports() {
+        let graph = graph(&["Step \"a\",b must be finished before step c can begin."]);
+        let schedule = run(&graph, 1, &Durations::new(2)).unwrap();
+
+        let mut out : Vec<u8> = vec![];
+        write_csv(&graph, &schedule, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "worker,step,start,end\n0,\"\"\"a\"\",b\",0,2\n0,c,2,7\n");
+
+        let mut out : Vec<u8> = vec![];
+        write_json(&graph, &schedule, &mut out).unwrap();
+        let json = String::from_utf8(out).unwrap();
+        assert!(json.contains("\"makespan\": 7,"));
+        assert!(json.contains("{\"worker\": 0, \"step\": \"\\\"a\\\",b\", \"start\": 0, \"end\": 2},\n"));
+        assert!(json.contains("{\"worker\": 0, \"step\": \"c\", \"start\": 2, \"end\": 7}\n  ]"));
+    }
+}
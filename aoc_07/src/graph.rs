@@ -0,0 +1,355 @@
+use std::collections::{BinaryHeap, HashMap};
+use std::cmp::Reverse;
+use std::fmt;
+use std::io::{Error, ErrorKind};
+use crate::from_string;
+
+pub type StepId = usize;
+
+// Steps that all (indirectly) wait on each other, so none of them can ever start. Each one
+// must be finished before the next, and the last before the first.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Cycle {
+    pub steps : Vec<String>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dependency cycle: ")?;
+        for step in self.steps.iter() {
+            write!(f, "{} -> ", step)?;
+        }
+        write!(f, "{}", self.steps[0])
+    }
+}
+
+impl From<Cycle> for Error {
+    fn from(cycle: Cycle) -> Self {
+        Error::new(ErrorKind::InvalidData, cycle.to_string())
+    }
+}
+
+// Steps are interned as they're seen, so ids follow first appearance in the input. Anything
+// that needs an order between steps should use their names (see `Ready`).
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    names : Vec<String>,
+    ids : HashMap<String, StepId>,
+    // Indexed by id: the steps waiting on this one, and the ones it waits on
+    dependents : Vec<Vec<StepId>>,
+    prereqs : Vec<Vec<StepId>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // One "Step X must be finished before step Y can begin." per line, blank lines ignored
+    pub fn parse(lines: &[String]) -> Result<Self, Error> {
+        let mut graph = Self::new();
+        for line in lines.iter().filter(|l| !l.trim().is_empty()) {
+            let (from, to) = from_string(line)?;
+            graph.add_edge(&from, &to);
+        }
+        Ok(graph)
+    }
+
+    pub fn intern(&mut self, name: &str) -> StepId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.dependents.push(vec![]);
+        self.prereqs.push(vec![]);
+        id
+    }
+
+    // `from` must be finished before `to` can begin. Repeats are ignored.
+    pub fn add_edge(&mut self, from: &str, to: &str) {
+        let from = self.intern(from);
+        let to = self.intern(to);
+        if !self.dependents[from].contains(&to) {
+            self.dependents[from].push(to);
+            self.prereqs[to].push(from);
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<StepId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: StepId) -> &str {
+        &self.names[id]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn steps(&self) -> std::ops::Range<StepId> {
+        0..self.len()
+    }
+
+    pub fn dependents(&self, id: StepId) -> &[StepId] {
+        &self.dependents[id]
+    }
+
+    pub fn prereqs(&self, id: StepId) -> &[StepId] {
+        &self.prereqs[id]
+    }
+
+    // Every step, each after everything it waits on, taking the first ready step by name
+    // whenever there's a choice
+    pub fn order(&self) -> Result<Vec<StepId>, Cycle> {
+        let mut counts : Vec<usize> = self.steps().map(|id| self.prereqs[id].len()).collect();
+        let mut ready = Ready::new(self);
+        for id in self.steps().filter(|id| counts[*id] == 0) {
+            ready.push(id);
+        }
+
+        let mut order = vec![];
+        while let Some(id) = ready.pop() {
+            order.push(id);
+            for next in self.dependents[id].iter() {
+                counts[*next] -= 1;
+                if counts[*next] == 0 {
+                    ready.push(*next);
+                }
+            }
+        }
+
+        if order.len() < self.len() {
+            let mut done = vec![false; self.len()];
+            for id in order.iter() {
+                done[*id] = true;
+            }
+            return Err(self.cycle(&done));
+        }
+        Ok(order)
+    }
+
+    // For when some steps never became ready. Every one of those waits on another of them, so
+    // following prerequisites back from any of them has to go round in a circle eventually.
+    pub fn cycle(&self, done: &[bool]) -> Cycle {
+        let by_name = |a: &&StepId, b: &&StepId| self.name(**a).cmp(self.name(**b));
+        let stuck : Vec<StepId> = self.steps().filter(|id| !done[*id]).collect();
+        let mut at = *stuck.iter().min_by(by_name).expect("some step is stuck");
+
+        let mut seen_at : Vec<Option<usize>> = vec![None; self.len()];
+        let mut path : Vec<StepId> = vec![];
+        while seen_at[at].is_none() {
+            seen_at[at] = Some(path.len());
+            path.push(at);
+            at = *self.prereqs[at].iter().filter(|p| !done[**p]).min_by(by_name).expect("stuck steps wait on stuck steps");
+        }
+
+        // The path went backwards along the edges, turn it round and start from the first name
+        let mut steps : Vec<StepId> = path.split_off(seen_at[at].unwrap());
+        steps.reverse();
+        let first = (0..steps.len()).min_by(|a, b| self.name(steps[*a]).cmp(self.name(steps[*b]))).unwrap();
+        steps.rotate_left(first);
+
+        Cycle { steps: steps.iter().map(|id| self.name(*id).to_string()).collect() }
+    }
+
+    pub fn names_of(&self, ids: &[StepId]) -> Vec<&str> {
+        ids.iter().map(|id| self.name(*id)).collect()
+    }
+
+    // Every (from, to) edge, sorted by name
+    pub fn edges(&self) -> Vec<(StepId, StepId)> {
+        let mut edges : Vec<(StepId, StepId)> = self.steps()
+            .flat_map(|from| self.dependents[from].iter().map(move |to| (from, *to)))
+            .collect();
+        edges.sort_by(|a, b| (self.name(a.0), self.name(a.1)).cmp(&(self.name(b.0), self.name(b.1))));
+        edges
+    }
+
+    // Everything that waits on each step, directly or not, as bits indexed by id. `order`
+    // has to put every step after its prerequisites.
+    pub fn reachable(&self, order: &[StepId]) -> Vec<Vec<u64>> {
+        let words = self.len().div_ceil(64);
+        let mut below : Vec<Vec<u64>> = vec![vec![0; words]; self.len()];
+        for id in order.iter().rev() {
+            let mut set = vec![0; words];
+            for d in self.dependents[*id].iter() {
+                set[d / 64] |= 1 << (d % 64);
+                for (word, other) in set.iter_mut().zip(below[*d].iter()) {
+                    *word |= other;
+                }
+            }
+            below[*id] = set;
+        }
+        below
+    }
+
+    // Edges that are already implied by a longer way round: `to` waits on something else
+    // that waits on `from`. Sorted by name.
+    pub fn redundant_edges(&self) -> Result<Vec<(StepId, StepId)>, Cycle> {
+        let below = self.reachable(&self.order()?);
+        Ok(self.edges().into_iter().filter(|(from, to)| {
+            self.dependents[*from].iter().any(|other| other != to && below[*other][to / 64] & (1 << (to % 64)) != 0)
+        }).collect())
+    }
+
+    // The same steps (with the same ids) with the redundant edges left out. Every order of
+    // this graph is still an order of the original and the other way round.
+    pub fn reduced(&self) -> Result<Graph, Cycle> {
+        let redundant = self.redundant_edges()?;
+        let mut graph = Graph::new();
+        for id in self.steps() {
+            graph.intern(self.name(id));
+        }
+        for (from, to) in self.edges() {
+            if !redundant.contains(&(from, to)) {
+                graph.add_edge(self.name(from), self.name(to));
+            }
+        }
+        Ok(graph)
+    }
+}
+
+// Steps that are free to start, handed out in order of name (or priority, then name)
+pub struct Ready {
+    rank : Vec<usize>,
+    by_rank : Vec<StepId>,
+    heap : BinaryHeap<Reverse<usize>>,
+}
+
+impl Ready {
+    pub fn new(graph: &Graph) -> Self {
+        Self::by_priority(graph, &vec![0; graph.len()])
+    }
+
+    // Highest priority first, by name among equals
+    pub fn by_priority(graph: &Graph, priorities: &[usize]) -> Self {
+        let mut by_rank : Vec<StepId> = graph.steps().collect();
+        by_rank.sort_by(|a, b| priorities[*b].cmp(&priorities[*a]).then_with(|| graph.name(*a).cmp(graph.name(*b))));
+        let mut rank = vec![0; graph.len()];
+        for (r, id) in by_rank.iter().enumerate() {
+            rank[*id] = r;
+        }
+
+        Self {
+            rank,
+            by_rank,
+            heap: BinaryHeap::new(),
+        }
+    }
+
+    // Position of the step among all the names, for breaking other ties the same way
+    pub fn rank(&self, id: StepId) -> usize {
+        self.rank[id]
+    }
+
+    pub fn push(&mut self, id: StepId) {
+        self.heap.push(Reverse(self.rank[id]));
+    }
+
+    pub fn pop(&mut self) -> Option<StepId> {
+        self.heap.pop().map(|r| self.by_rank[r.0])
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::graph::Graph;
+
+    pub const EXAMPLE : [&str; 7] = [
+        "Step C must be finished before step A can begin.",
+        "Step C must be finished before step F can begin.",
+        "Step A must be finished before step B can begin.",
+        "Step A must be finished before step D can begin.",
+        "Step B must be finished before step E can begin.",
+        "Step D must be finished before step E can begin.",
+        "Step F must be finished before step E can begin.",
+    ];
+
+    pub fn graph(lines: &[&str]) -> Graph {
+        Graph::parse(&lines.iter().map(|s| s.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn example_order() {
+        let graph = graph(&EXAMPLE);
+        assert_eq!(graph.names_of(&graph.order().unwrap()).concat(), "CABDFE");
+        assert_eq!(graph.len(), 6);
+        assert_eq!(graph.name(graph.id("F").unwrap()), "F");
+    }
+
+    #[test]
+    fn named_steps() {
+        let graph = graph(&[
+            "Step fetch-deps must be finished before step build_core can begin.",
+            "Step fetch-deps must be finished before step build can begin.",
+            "Step build_core must be finished before step test can begin.",
+            "Step build must be finished before step test can begin.",
+            "Step build must be finished before step test can begin.",
+            "Step lint must be finished before step test can begin.",
+        ]);
+        // "build" < "build_core" < "lint", whole names and not first letters
+        assert_eq!(graph.names_of(&graph.order().unwrap()), vec!["fetch-deps", "build", "build_core", "lint", "test"]);
+        assert_eq!(graph.prereqs(graph.id("test").unwrap()).len(), 3);
+    }
+
+    #[test]
+    fn name_beats_depth() {
+        // Depth first from "a" would go straight on to "c", but "b" is ready by then too
+        let graph = graph(&[
+            "Step d must be finished before step b can begin.",
+            "Step a must be finished before step c can begin.",
+            "Step d must be finished before step a can begin.",
+        ]);
+        assert_eq!(graph.names_of(&graph.order().unwrap()).concat(), "dabc");
+    }
+
+    #[test]
+    fn reduction() {
+        let mut lines = EXAMPLE.to_vec();
+        lines.push("Step C must be finished before step E can begin.");
+        lines.push("Step A must be finished before step E can begin.");
+        lines.push("Step C must be finished before step D can begin.");
+        let graph = graph(&lines);
+
+        let redundant : Vec<String> = graph.redundant_edges().unwrap().iter()
+            .map(|(from, to)| format!("{}{}", graph.name(*from), graph.name(*to))).collect();
+        assert_eq!(redundant, vec!["AE", "CD", "CE"]);
+
+        let reduced = graph.reduced().unwrap();
+        assert_eq!(reduced.edges().len(), EXAMPLE.len());
+        assert!(reduced.redundant_edges().unwrap().is_empty());
+        assert_eq!(reduced.id("E"), graph.id("E"));
+        assert_eq!(reduced.order(), graph.order());
+    }
+
+    #[test]
+    fn cycles() {
+        // E -> G -> H -> E, hanging off the example
+        let mut lines = EXAMPLE.to_vec();
+        lines.push("Step E must be finished before step G can begin.");
+        lines.push("Step G must be finished before step H can begin.");
+        lines.push("Step H must be finished before step E can begin.");
+        lines.push("Step H must be finished before step Z can begin.");
+        let cycle = graph(&lines).order().unwrap_err();
+        assert_eq!(cycle.steps, vec!["E", "G", "H"]);
+        assert_eq!(cycle.to_string(), "dependency cycle: E -> G -> H -> E");
+
+        let cycle = graph(&["Step x must be finished before step x can begin."]).order().unwrap_err();
+        assert_eq!(cycle.to_string(), "dependency cycle: x -> x");
+    }
+}
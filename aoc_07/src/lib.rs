@@ -0,0 +1,31 @@
+use std::io::{Read, BufReader, BufRead, Error, ErrorKind};
+use regex::Regex;
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod critical;
+pub mod dot;
+pub mod gantt;
+pub mod graph;
+pub mod orders;
+pub mod policy;
+pub mod schedule;
+
+pub fn read<R: Read>(io: R) -> Vec<String> {
+    let br = BufReader::new(io);
+    br.lines().map(|l| l.unwrap()).collect()
+}
+
+// Drastically reduces runtime to not have to compile this thing everytime
+lazy_static! {
+    static ref PARSE_RE: Regex = Regex::new(r"^\s*Step (\S+) must be finished before step (\S+) can begin\.?\s*$").unwrap();
+}
+
+// Step names are anything without whitespace
+pub fn from_string(s: &str) -> Result<(String, String), Error> {
+    let caps = PARSE_RE.captures(s)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("not a step: {}", s)))?;
+
+    Ok((caps[1].to_string(), caps[2].to_string()))
+}
@@ -1,170 +1,140 @@
 use std::fs::File;
-use std::io::{Read, BufReader, BufRead};
-use regex::Regex;
-use std::collections::HashMap;
-use std::collections::BinaryHeap;
-use std::cmp::{Ord, Ordering, PartialOrd, Reverse};
-
-#[macro_use]
-extern crate lazy_static;
-
-fn read<R: Read>(io: R) -> Vec<String> {
-    let br = BufReader::new(io);
-    br.lines().map(|l| l.unwrap()).collect()
-}
-
-// Drastically reduces runtime to not have to compile this thing everytime
-lazy_static! {
-    static ref parse_re: Regex = Regex::new(r"Step ([a-zA-Z]) must be finished before step ([a-zA-Z]) can begin.").unwrap();
+use std::io::BufWriter;
+
+use aoc_07::{critical, dot, gantt, orders, policy, read};
+use aoc_07::graph::{Cycle, Graph};
+use aoc_07::policy::{Alphabetical, Policy};
+use aoc_07::schedule::{self, Durations, Schedule};
+
+fn part_1(graph: &Graph) -> Result<(), Cycle> {
+    let names = graph.names_of(&graph.order()?);
+    // Single letter steps read best run together, like the puzzle wants
+    if names.iter().all(|name| name.chars().count() == 1) {
+        println!("{}", names.concat());
+    } else {
+        println!("{}", names.join(" "));
+    }
+    Ok(())
 }
 
-fn from_string(s: &String) -> (char, char) {
-    let caps = parse_re.captures(s).unwrap();
-
-    // println!("Making new claim {}", s);
-
-    let from = caps[1].chars().next().unwrap();
-    let to = caps[2].chars().next().unwrap();
-
-    (from, to)
+fn part_2(graph: &Graph, workers: usize, durations: &Durations, policy: &dyn Policy) -> Result<Schedule, Cycle> {
+    let schedule = schedule::run_with(graph, workers, durations, policy)?;
+    println!("Time taken: {}", schedule.makespan);
+    Ok(schedule)
 }
 
-#[derive(Copy, Clone)]
-enum State {
-    Nothing,
-    Queued,
-    Done,
-}
+fn critical_path(graph: &Graph, durations: &Durations) -> Result<(), Cycle> {
+    let analysis = critical::analyse(graph, durations)?;
+    let width = graph.steps().map(|id| graph.name(id).len()).max().unwrap_or(0).max(4);
 
-fn depth_first(start: char, graph : &HashMap<char, Vec<char>>, state : &mut [State], done: &mut Vec<char>) {
-    match state[start as usize] {
-        State::Queued => return (),
-        State::Done => return (),
-        _ => {},
+    println!("{:width$}  duration  earliest  latest  slack", "step", width = width);
+    for id in graph.order()? {
+        let timing = &analysis.timings[id];
+        println!("{:width$}  {:>8}  {:>8}  {:>6}  {:>5}", graph.name(id), timing.duration,
+                 timing.earliest_start, timing.latest_start, timing.slack(), width = width);
     }
-
-    state[start as usize] = State::Queued;
-
-    for neigh in graph[&start].iter() {
-        depth_first(*neigh, graph, state, done);
-    }
-
-    done.push(start);
-    state[start as usize] = State::Done;
+    println!("Critical path: {}", graph.names_of(&analysis.critical_path).join(" -> "));
+    println!("Time with unlimited workers: {}", analysis.lower_bound);
+    Ok(())
 }
 
-fn part_1(graph: &mut HashMap<char, Vec<char>>) {
-    let mut states : [State; 256] = [State::Nothing; 256];
-    let mut order : Vec<char> = vec![];
-
-    // Get the list of keys
-    let mut tasks : Vec<char> = graph.keys().into_iter().map(|x| *x).collect();
-    tasks.sort();
-    tasks.reverse();
-
-    for task in tasks.iter() {
-        graph.get_mut(task).unwrap().sort();
-        graph.get_mut(task).unwrap().reverse();
+// How constrained the graph is: the number of valid orders, and the first few of them
+fn orders(graph: &Graph, modulus: Option<u64>, list: usize) -> Result<(), Cycle> {
+    match modulus {
+        Some(modulus) => println!("Valid orders (mod {}): {}", modulus, orders::count_orders_mod(graph, modulus)?),
+        None => println!("Valid orders: {}", orders::count_orders(graph)?),
     }
-
-    for task in tasks.iter() {
-        depth_first(*task, &graph, &mut states, &mut order);
+    for order in orders::Orders::new(graph)?.take(list) {
+        println!("{}", graph.names_of(&order).join(" "));
     }
-
-    for task in order.iter().rev() {
-        print!("{}", task);
-    }
-
-    println!();
-}
-
-#[derive(Eq, PartialEq)]
-struct Event {
-    time : usize,
-    task : char,
+    Ok(())
 }
 
-impl Ord for Event {
-    // Flipped for use in Max BinaryHeap
-    fn cmp(&self, other: &Event) -> Ordering {
-        other.time.cmp(&self.time)
-            .then_with(|| other.task.cmp(&self.task))
+// Lists the edges implied by others, and hands back the graph without them
+fn reduce(graph: &Graph) -> Result<Graph, Cycle> {
+    let redundant = graph.redundant_edges()?;
+    println!("{} of {} edges are redundant", redundant.len(), graph.edges().len());
+    for (from, to) in redundant.iter() {
+        println!("    {} -> {}", graph.name(*from), graph.name(*to));
     }
+    graph.reduced()
 }
 
-impl PartialOrd for Event {
-    fn partial_cmp(&self, other: &Event) -> Option<Ordering> {
-        Some(self.cmp(other))
+fn compare_policies(graph: &Graph, workers: usize, durations: &Durations) -> Result<(), Cycle> {
+    let lower_bound = critical::analyse(graph, durations)?.lower_bound;
+    for (name, makespan) in policy::compare(graph, workers, durations, &policy::all())? {
+        println!("{:16} {:>6}  (+{})", name, makespan, makespan - lower_bound);
     }
+    println!("{:16} {:>6}", "unlimited", lower_bound);
+    Ok(())
 }
 
-fn part_2(graph: &mut HashMap<char, Vec<char>>, rev_graph: &mut HashMap<char, Vec<char>>) {
-    let mut pq = BinaryHeap::new();
-    let mut to_q : BinaryHeap<Reverse<char>> = BinaryHeap::new();
-    let mut workers_avail: usize = 5;
-    let mut counts: [usize; 256] = [0; 256];
-
-    for (task, neighs) in graph.iter() {
-        counts[*task as usize] = neighs.len();
-        if neighs.len() == 0 {
-            to_q.push(Reverse(*task));
+fn main() -> std::io::Result<()> {
+    let args : Vec<String> = std::env::args().skip(1).collect();
+    let arg_value = |flag: &str| args.iter().position(|arg| arg == flag).and_then(|i| args.get(i + 1));
+    let number = |flag: &str, default: usize| -> std::io::Result<usize> {
+        match arg_value(flag) {
+            Some(value) => value.parse().map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{}: {}", flag, e))),
+            None => Ok(default),
         }
-    }
+    };
 
-    while workers_avail != 0 && !to_q.is_empty() {
-        let task = to_q.pop().unwrap().0;
-        pq.push(Event {time: 60 + task as usize - 'A' as usize + 1, task: task});
-        workers_avail -= 1;
+    let workers = number("--workers", 5)?;
+    if workers == 0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "--workers: need at least one"));
     }
-
-    let mut curr_time: usize = 0;
-
-    while !pq.is_empty() {
-        let event = pq.pop().unwrap();
-        workers_avail += 1;
-        curr_time = event.time;
-        // println!("Task {} finished at time {}, workers: {}", event.task, event.time, workers_avail);
-
-        // Update the counts based on this task completing
-        for neigh in rev_graph[&event.task].iter() {
-            counts[*neigh as usize] -= 1;
-            if counts[*neigh as usize] == 0 {
-                // This was the last neighbor, we can now push.
-                to_q.push(Reverse(*neigh));
-            }
-        }
-
-        while workers_avail != 0 && !to_q.is_empty() {
-            let task = to_q.pop().unwrap().0;
-            pq.push(Event {time: curr_time + 60 + (task as usize) - ('A' as usize) + 1, task: task});
-            workers_avail -= 1;
-            // println!("Worker taking job {} at time {}, rem: {}", task, curr_time, workers_avail);
-        }
+    let mut durations = Durations::new(number("--base", 60)?);
+    if let Some(path) = arg_value("--durations") {
+        durations.load(&read(File::open(path)?))?;
     }
 
-    println!("Time taken: {}", curr_time);
-}
+    let policy = match arg_value("--policy") {
+        Some(name) => policy::by_name(name).ok_or_else(|| {
+            let names : Vec<String> = policy::all().iter().map(|p| p.name().to_string()).collect();
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("--policy: expected one of {}", names.join(", ")))
+        })?,
+        None => Box::new(Alphabetical),
+    };
 
-fn main() -> std::io::Result<()> {
     let f = File::open("./input.txt")?;
-    let edges : Vec<_> = read(f).iter().map(|s| from_string(s)).collect();
-
-    let mut graph = HashMap::new();
-    let mut rev_graph = HashMap::new();
-
-    for edge in edges {
-        let tos = graph.entry(edge.0).or_insert(vec![]);
-        let froms = rev_graph.entry(edge.1).or_insert(vec![]);
-        tos.push(edge.1);
-        froms.push(edge.0);
-        {
-            graph.entry(edge.1).or_insert(vec![]);
-            rev_graph.entry(edge.0).or_insert(vec![]);
+    let graph = Graph::parse(&read(f))?;
+
+    part_1(&graph)?;
+    let schedule = part_2(&graph, workers, &durations, policy.as_ref())?;
+
+    let reduced = if args.iter().any(|arg| arg == "--reduce") {
+        Some(reduce(&graph)?)
+    } else {
+        None
+    };
+    if let Some(path) = arg_value("--dot") {
+        dot::write_dot(reduced.as_ref().unwrap_or(&graph), &mut BufWriter::new(File::create(path)?))?;
+    }
+    if args.iter().any(|arg| arg == "--count") {
+        let modulus = match arg_value("--mod") {
+            Some(_) => Some(number("--mod", 0)? as u64),
+            None => None,
+        };
+        if modulus == Some(0) {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "--mod: must be positive"));
         }
+        orders(&graph, modulus, number("--list", 0)?)?;
+    }
+    if args.iter().any(|arg| arg == "--compare") {
+        compare_policies(&graph, workers, &durations)?;
+    }
+    if args.iter().any(|arg| arg == "--critical") {
+        critical_path(&graph, &durations)?;
+    }
+    if args.iter().any(|arg| arg == "--gantt") {
+        print!("{}", gantt::text(&graph, &schedule, number("--scale", 1)?));
+    }
+    if let Some(path) = arg_value("--csv") {
+        gantt::write_csv(&graph, &schedule, &mut BufWriter::new(File::create(path)?))?;
+    }
+    if let Some(path) = arg_value("--json") {
+        gantt::write_json(&graph, &schedule, &mut BufWriter::new(File::create(path)?))?;
     }
-
-    part_1(&mut graph);
-    part_2(&mut rev_graph, &mut graph);
 
     Ok(())
 }
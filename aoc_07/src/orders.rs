@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::graph::{Cycle, Graph, StepId};
+
+// An unsigned integer as big as it needs to be. Counting orders only ever adds, so that's
+// all it does.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BigCount {
+    // Base 2^32, least significant first
+    limbs : Vec<u32>,
+}
+
+impl BigCount {
+    pub fn new(n: u64) -> Self {
+        let mut count = Self { limbs: vec![n as u32, (n >> 32) as u32] };
+        count.trim();
+        count
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    pub fn add(&mut self, other: &BigCount) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry = 0u64;
+        for i in 0..self.limbs.len() {
+            let sum = self.limbs[i] as u64 + *other.limbs.get(i).unwrap_or(&0) as u64 + carry;
+            self.limbs[i] = sum as u32;
+            carry = sum >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        if self.limbs.len() > 4 {
+            return None;
+        }
+        Some(self.limbs.iter().rev().fold(0u128, |n, limb| (n << 32) | *limb as u128))
+    }
+}
+
+impl fmt::Display for BigCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Peel off nine decimal digits at a time
+        let mut limbs = self.limbs.clone();
+        let mut chunks : Vec<u32> = vec![];
+        while !limbs.is_empty() {
+            let mut rem = 0u64;
+            for limb in limbs.iter_mut().rev() {
+                let cur = (rem << 32) | *limb as u64;
+                *limb = (cur / 1_000_000_000) as u32;
+                rem = cur % 1_000_000_000;
+            }
+            chunks.push(rem as u32);
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+
+        match chunks.pop() {
+            None => write!(f, "0"),
+            Some(first) => {
+                write!(f, "{}", first)?;
+                for chunk in chunks.iter().rev() {
+                    write!(f, "{:09}", chunk)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+type StepSet = Vec<u64>;
+
+fn contains(set: &StepSet, id: StepId) -> bool {
+    set[id / 64] & (1 << (id % 64)) != 0
+}
+
+// The number of orders is the number of ways to get from nothing done to everything done,
+// one ready step at a time. Going a step at a time, only the sets of finished steps that can
+// actually happen get counted, and only one layer of them is kept around. That's still a lot
+// of sets for a wide graph with few edges.
+fn count_with<T: Clone>(graph: &Graph, one: T, add: impl Fn(&mut T, &T)) -> Result<T, Cycle> {
+    graph.order()?;
+
+    let words = graph.len().div_ceil(64);
+    let mut layer : HashMap<StepSet, T> = HashMap::new();
+    layer.insert(vec![0; words], one);
+
+    for _ in graph.steps() {
+        let mut next : HashMap<StepSet, T> = HashMap::new();
+        for (done, count) in layer.iter() {
+            let ready = graph.steps().filter(|id| !contains(done, *id)
+                && graph.prereqs(*id).iter().all(|p| contains(done, *p)));
+            for id in ready {
+                let mut after = done.clone();
+                after[id / 64] |= 1 << (id % 64);
+                match next.get_mut(&after) {
+                    Some(total) => add(total, count),
+                    None => {
+                        next.insert(after, count.clone());
+                    },
+                }
+            }
+        }
+        layer = next;
+    }
+
+    // Everything done is the only set left
+    Ok(layer.into_iter().next().unwrap().1)
+}
+
+pub fn count_orders(graph: &Graph) -> Result<BigCount, Cycle> {
+    count_with(graph, BigCount::new(1), |total, count| total.add(count))
+}
+
+// The count modulo `modulus`, for when only a fingerprint of it is needed
+pub fn count_orders_mod(graph: &Graph, modulus: u64) -> Result<u64, Cycle> {
+    assert!(modulus > 0, "modulus must be positive");
+    count_with(graph, 1 % modulus, |total, count| *total = ((*total as u128 + *count as u128) % modulus as u128) as u64)
+}
+
+// Every valid order, one at a time, starting with the one `Graph::order` gives and going up
+// by name from there
+pub struct Orders<'a> {
+    graph : &'a Graph,
+    by_name : Vec<StepId>,
+    // Unfinished prerequisites of each step
+    waiting : Vec<usize>,
+    placed : Vec<bool>,
+    prefix : Vec<StepId>,
+    // For each position, the steps that could go there and which one to try next
+    choices : Vec<(Vec<StepId>, usize)>,
+    empty_done : bool,
+}
+
+impl<'a> Orders<'a> {
+    pub fn new(graph: &'a Graph) -> Result<Self, Cycle> {
+        graph.order()?;
+
+        let mut by_name : Vec<StepId> = graph.steps().collect();
+        by_name.sort_by(|a, b| graph.name(*a).cmp(graph.name(*b)));
+
+        let mut orders = Self {
+            graph,
+            by_name,
+            waiting: graph.steps().map(|id| graph.prereqs(id).len()).collect(),
+            placed: vec![false; graph.len()],
+            prefix: vec![],
+            choices: vec![],
+            empty_done: false,
+        };
+        let ready = orders.ready();
+        orders.choices.push((ready, 0));
+        Ok(orders)
+    }
+
+    fn ready(&self) -> Vec<StepId> {
+        self.by_name.iter().copied().filter(|id| !self.placed[*id] && self.waiting[*id] == 0).collect()
+    }
+
+    fn place(&mut self, id: StepId) {
+        self.placed[id] = true;
+        self.prefix.push(id);
+        for d in self.graph.dependents(id).iter() {
+            self.waiting[*d] -= 1;
+        }
+    }
+
+    fn unplace(&mut self) {
+        let id = self.prefix.pop().unwrap();
+        self.placed[id] = false;
+        for d in self.graph.dependents(id).iter() {
+            self.waiting[*d] += 1;
+        }
+    }
+}
+
+impl<'a> Iterator for Orders<'a> {
+    type Item = Vec<StepId>;
+
+    fn next(&mut self) -> Option<Vec<StepId>> {
+        if self.graph.is_empty() {
+            // Exactly one way to do nothing
+            let first = !self.empty_done;
+            self.empty_done = true;
+            return if first { Some(vec![]) } else { None };
+        }
+
+        loop {
+            // Take back whatever was tried at the deepest position last time
+            if !self.prefix.is_empty() && self.prefix.len() == self.choices.len() {
+                self.unplace();
+            }
+
+            let (candidates, next) = self.choices.last_mut()?;
+            if *next == candidates.len() {
+                self.choices.pop();
+                continue;
+            }
+            let id = candidates[*next];
+            *next += 1;
+
+            self.place(id);
+            if self.prefix.len() == self.graph.len() {
+                return Some(self.prefix.clone());
+            }
+            let ready = self.ready();
+            self.choices.push((ready, 0));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::Graph;
+    use crate::graph::tests::{graph, EXAMPLE};
+    use crate::orders::{count_orders, count_orders_mod, BigCount, Orders};
+
+    #[test]
+    fn example() {
+        let graph = graph(&EXAMPLE);
+        let orders : Vec<String> = Orders::new(&graph).unwrap().map(|o| graph.names_of(&o).concat()).collect();
+        assert_eq!(orders, vec!["CABDFE", "CABFDE", "CADBFE", "CADFBE", "CAFBDE", "CAFDBE", "CFABDE", "CFADBE"]);
+        assert_eq!(count_orders(&graph).unwrap(), BigCount::new(8));
+        assert_eq!(count_orders_mod(&graph, 5).unwrap(), 3);
+    }
+
+    #[test]
+    fn chains() {
+        // Four separate chains of ten steps can be interleaved in 40! / (10!)^4 ways, which is
+        // too many for a u64
+        let mut graph = Graph::new();
+        for chain in ["a", "b", "c", "d"].iter() {
+            for i in 1..10 {
+                graph.add_edge(&format!("{}{}", chain, i - 1), &format!("{}{}", chain, i));
+            }
+        }
+        let count = count_orders(&graph).unwrap();
+        assert_eq!(count.to_string(), "4705360871073570227520");
+        assert_eq!(count.to_u128(), Some(4705360871073570227520));
+        assert_eq!(count_orders_mod(&graph, 1_000_000_007).unwrap(), 44360568);
+
+        let first = Orders::new(&graph).unwrap().next().unwrap();
+        assert_eq!(graph.names_of(&first)[..3], ["a0", "a1", "a2"]);
+        assert_eq!(Orders::new(&Graph::new()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn big_counts() {
+        let mut n = BigCount::new(u64::MAX);
+        n.add(&BigCount::new(1));
+        assert_eq!(n.to_string(), "18446744073709551616");
+        assert_eq!(BigCount::new(0).to_string(), "0");
+        assert_eq!(BigCount::new(1_000_000_000).to_string(), "1000000000");
+    }
+}
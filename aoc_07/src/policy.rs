@@ -0,0 +1,128 @@
+use crate::graph::{Cycle, Graph, StepId};
+use crate::schedule::{self, Durations};
+
+// Decides which ready step a free worker takes. Priorities are fixed up front: the highest
+// ready one goes first, and equal priorities go by name.
+pub trait Policy {
+    fn name(&self) -> &str;
+
+    // One per step, by id. `lengths` are the step durations, `order` is every step after its
+    // prerequisites.
+    fn priorities(&self, graph: &Graph, lengths: &[usize], order: &[StepId]) -> Vec<usize>;
+}
+
+// The puzzle's rule, just names
+pub struct Alphabetical;
+
+pub struct LongestFirst;
+
+// Counts everything that waits on a step, directly or not
+pub struct MostDependents;
+
+// Prefers steps with the longest chain still to run after them, themselves included
+pub struct CriticalPath;
+
+impl Policy for Alphabetical {
+    fn name(&self) -> &str {
+        "alphabetical"
+    }
+
+    fn priorities(&self, graph: &Graph, _lengths: &[usize], _order: &[StepId]) -> Vec<usize> {
+        vec![0; graph.len()]
+    }
+}
+
+impl Policy for LongestFirst {
+    fn name(&self) -> &str {
+        "longest-first"
+    }
+
+    fn priorities(&self, _graph: &Graph, lengths: &[usize], _order: &[StepId]) -> Vec<usize> {
+        lengths.to_vec()
+    }
+}
+
+impl Policy for MostDependents {
+    fn name(&self) -> &str {
+        "most-dependents"
+    }
+
+    fn priorities(&self, graph: &Graph, _lengths: &[usize], order: &[StepId]) -> Vec<usize> {
+        let below = graph.reachable(order);
+        below.iter().map(|set| set.iter().map(|w| w.count_ones() as usize).sum()).collect()
+    }
+}
+
+impl Policy for CriticalPath {
+    fn name(&self) -> &str {
+        "critical-path"
+    }
+
+    fn priorities(&self, graph: &Graph, lengths: &[usize], order: &[StepId]) -> Vec<usize> {
+        let mut remaining = vec![0; graph.len()];
+        for id in order.iter().rev() {
+            remaining[*id] = lengths[*id] + graph.dependents(*id).iter().map(|d| remaining[*d]).max().unwrap_or(0);
+        }
+        remaining
+    }
+}
+
+pub fn all() -> Vec<Box<dyn Policy>> {
+    vec![Box::new(Alphabetical), Box::new(LongestFirst), Box::new(MostDependents), Box::new(CriticalPath)]
+}
+
+pub fn by_name(name: &str) -> Option<Box<dyn Policy>> {
+    all().into_iter().find(|policy| policy.name() == name)
+}
+
+// (policy name, time taken) for each policy on the same graph and workers
+pub fn compare(graph: &Graph, workers: usize, durations: &Durations, policies: &[Box<dyn Policy>])
+    -> Result<Vec<(String, usize)>, Cycle> {
+    policies.iter().map(|policy| {
+        let schedule = schedule::run_with(graph, workers, durations, policy.as_ref())?;
+        Ok((policy.name().to_string(), schedule.makespan))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::tests::{graph, EXAMPLE};
+    use crate::policy::{all, by_name, compare, Policy, CriticalPath, LongestFirst, MostDependents};
+    use crate::schedule::Durations;
+
+    #[test]
+    fn priorities() {
+        let graph = graph(&EXAMPLE);
+        let lengths = Durations::new(0).for_graph(&graph);
+        let order = graph.order().unwrap();
+        let by_step = |policy: &dyn Policy| -> String {
+            let priorities = policy.priorities(&graph, &lengths, &order);
+            ["A", "B", "C", "D", "E", "F"].iter().map(|s| priorities[graph.id(s).unwrap()].to_string()).collect::<Vec<_>>().join(" ")
+        };
+        assert_eq!(by_step(&LongestFirst), "1 2 3 4 5 6");
+        assert_eq!(by_step(&MostDependents), "3 1 5 1 0 1");
+        assert_eq!(by_step(&CriticalPath), "10 7 14 9 5 11");
+    }
+
+    #[test]
+    fn comparison() {
+        // Alphabetical starts a and b together and leaves z until a's done
+        let graph = graph(&[
+            "Step a must be finished before step end can begin.",
+            "Step b must be finished before step end can begin.",
+            "Step z must be finished before step end can begin.",
+        ]);
+        let mut durations = Durations::new(0);
+        durations.load(&["a 1".to_string(), "b 1".to_string(), "z 10".to_string(), "end 1".to_string()]).unwrap();
+
+        let results = compare(&graph, 2, &durations, &all()).unwrap();
+        assert_eq!(results, vec![
+            ("alphabetical".to_string(), 12),
+            ("longest-first".to_string(), 11),
+            ("most-dependents".to_string(), 12),
+            ("critical-path".to_string(), 11),
+        ]);
+        assert!(by_name("critical-path").is_some());
+        assert!(by_name("random").is_none());
+    }
+}
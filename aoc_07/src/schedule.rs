@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::collections::BinaryHeap;
+use std::cmp::{Ord, Ordering, PartialOrd, Reverse};
+use std::io::{Error, ErrorKind};
+use crate::graph::{Cycle, Graph, Ready, StepId};
+use crate::policy::{Alphabetical, Policy};
+
+// How long each step takes. Steps named in the table take exactly that long. Otherwise it's
+// the base, plus the position in the alphabet (A = 1, B = 2, ...) for single letter steps.
+#[derive(Debug, Clone)]
+pub struct Durations {
+    pub base : usize,
+    pub table : HashMap<String, usize>,
+}
+
+impl Durations {
+    pub fn new(base: usize) -> Self {
+        Self {
+            base,
+            table: HashMap::new(),
+        }
+    }
+
+    // The puzzle's model: 60 seconds plus the letter
+    pub fn puzzle() -> Self {
+        Self::new(60)
+    }
+
+    // Reads "<step> <seconds>" lines into the table. Blank lines and lines starting with '#'
+    // are skipped.
+    pub fn load(&mut self, lines: &[String]) -> Result<(), Error> {
+        for (i, line) in lines.iter().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let bad = |what: &str| Error::new(ErrorKind::InvalidInput, format!("line {}: {}", i + 1, what));
+            let fields : Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 2 {
+                return Err(bad("expected a step and a duration"));
+            }
+            let seconds = fields[1].parse().map_err(|e| bad(&format!("{}", e)))?;
+            self.table.insert(fields[0].to_string(), seconds);
+        }
+        Ok(())
+    }
+
+    pub fn of(&self, step: &str) -> usize {
+        if let Some(seconds) = self.table.get(step) {
+            return *seconds;
+        }
+        let mut chars = step.chars();
+        match (chars.next(), chars.next()) {
+            (Some(letter), None) if letter.is_ascii_alphabetic() =>
+                self.base + (letter.to_ascii_uppercase() as usize - 'A' as usize + 1),
+            _ => self.base,
+        }
+    }
+
+    // Durations of every step in the graph, by id
+    pub fn for_graph(&self, graph: &Graph) -> Vec<usize> {
+        graph.steps().map(|id| self.of(graph.name(id))).collect()
+    }
+}
+
+// One step being worked on: `worker` (numbered from 0) spent seconds start..end on it
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Slot {
+    pub worker : usize,
+    pub step : StepId,
+    pub start : usize,
+    pub end : usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub workers : usize,
+    // In order of starting time
+    pub slots : Vec<Slot>,
+    pub makespan : usize,
+}
+
+impl Schedule {
+    pub fn busy(&self, worker: usize) -> usize {
+        self.slots.iter().filter(|s| s.worker == worker).map(|s| s.end - s.start).sum()
+    }
+
+    // Seconds the worker spent waiting before everything was done
+    pub fn idle(&self, worker: usize) -> usize {
+        self.makespan - self.busy(worker)
+    }
+}
+
+#[derive(Eq, PartialEq)]
+struct Event {
+    time : usize,
+    // Steps finishing together are dealt with in the order they'd be picked
+    rank : usize,
+    task : StepId,
+    worker : usize,
+}
+
+impl Ord for Event {
+    // Flipped for use in Max BinaryHeap
+    fn cmp(&self, other: &Event) -> Ordering {
+        other.time.cmp(&self.time)
+            .then_with(|| other.rank.cmp(&self.rank))
+    }
+}
+
+impl PartialOrd for Event {
+    fn partial_cmp(&self, other: &Event) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Who does what when, with `workers` people always starting the first step by name that's
+// ready. A free step goes to the lowest numbered free worker.
+pub fn run(graph: &Graph, workers: usize, durations: &Durations) -> Result<Schedule, Cycle> {
+    run_with(graph, workers, durations, &Alphabetical)
+}
+
+// The same, but choosing between ready steps by the policy's priorities
+pub fn run_with(graph: &Graph, workers: usize, durations: &Durations, policy: &dyn Policy) -> Result<Schedule, Cycle> {
+    assert!(workers > 0, "need at least one worker");
+    let order = graph.order()?;
+    let lengths = durations.for_graph(graph);
+    let mut pq = BinaryHeap::new();
+    let mut to_q = Ready::by_priority(graph, &policy.priorities(graph, &lengths, &order));
+    let mut free : BinaryHeap<Reverse<usize>> = (0..workers).map(Reverse).collect();
+    let mut counts: Vec<usize> = graph.steps().map(|id| graph.prereqs(id).len()).collect();
+
+    for task in graph.steps().filter(|id| counts[*id] == 0) {
+        to_q.push(task);
+    }
+
+    let mut curr_time: usize = 0;
+    let mut slots = vec![];
+
+    loop {
+        while !free.is_empty() && !to_q.is_empty() {
+            let task = to_q.pop().unwrap();
+            let worker = free.pop().unwrap().0;
+            let end = curr_time + lengths[task];
+            pq.push(Event {time: end, rank: to_q.rank(task), task, worker});
+            slots.push(Slot { worker, step: task, start: curr_time, end });
+        }
+
+        let event = match pq.pop() {
+            Some(event) => event,
+            None => break,
+        };
+        free.push(Reverse(event.worker));
+        curr_time = event.time;
+
+        // Update the counts based on this task completing
+        for neigh in graph.dependents(event.task).iter() {
+            counts[*neigh] -= 1;
+            if counts[*neigh] == 0 {
+                // This was the last neighbor, we can now push.
+                to_q.push(*neigh);
+            }
+        }
+    }
+
+    Ok(Schedule { workers, slots, makespan: curr_time })
+}
+
+// Just the time taken to finish everything
+pub fn simulate(graph: &Graph, workers: usize, durations: &Durations) -> Result<usize, Cycle> {
+    run(graph, workers, durations).map(|schedule| schedule.makespan)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::tests::{graph, EXAMPLE};
+    use crate::schedule::{run, simulate, Durations, Slot};
+
+    #[test]
+    fn example() {
+        let graph = graph(&EXAMPLE);
+        assert_eq!(simulate(&graph, 2, &Durations::new(0)), Ok(15));
+        // One worker just does everything in turn: 1 + 2 + ... + 6, plus 60 each
+        assert_eq!(simulate(&graph, 1, &Durations::puzzle()), Ok(21 + 6 * 60));
+    }
+
+    #[test]
+    fn slots() {
+        let graph = graph(&EXAMPLE);
+        let schedule = run(&graph, 2, &Durations::new(0)).unwrap();
+        let id = |name| graph.id(name).unwrap();
+        // The puzzle's walkthrough: C, then A alongside F, B and D, and E last
+        assert_eq!(schedule.slots, vec![
+            Slot { worker: 0, step: id("C"), start: 0, end: 3 },
+            Slot { worker: 0, step: id("A"), start: 3, end: 4 },
+            Slot { worker: 1, step: id("F"), start: 3, end: 9 },
+            Slot { worker: 0, step: id("B"), start: 4, end: 6 },
+            Slot { worker: 0, step: id("D"), start: 6, end: 10 },
+            Slot { worker: 0, step: id("E"), start: 10, end: 15 },
+        ]);
+        assert_eq!(schedule.idle(0), 0);
+        assert_eq!(schedule.idle(1), 9);
+    }
+
+    #[test]
+    fn table() {
+        let mut durations = Durations::new(10);
+        let lines : Vec<String> = ["# overrides", "", "C 1", "E   100", "deploy 7"].iter().map(|s| s.to_string()).collect();
+        durations.load(&lines).unwrap();
+        assert_eq!(durations.of("C"), 1);
+        assert_eq!(durations.of("E"), 100);
+        assert_eq!(durations.of("B"), 12);
+        assert_eq!(durations.of("deploy"), 7);
+        assert_eq!(durations.of("build"), 10);
+
+        // C, then A and F, then B and D, then E
+        assert_eq!(simulate(&graph(&EXAMPLE), 2, &durations), Ok(1 + 16 + 14 + 100));
+
+        let err = durations.load(&["A 1".to_string(), "A B 2".to_string()]).unwrap_err();
+        assert!(err.to_string().starts_with("line 2"));
+        assert!(durations.load(&["A x".to_string()]).is_err());
+    }
+
+    #[test]
+    fn cycle() {
+        // B and C can run, but nothing after them
+        let graph = graph(&[
+            "Step A must be finished before step B can begin.",
+            "Step A must be finished before step C can begin.",
+            "Step C must be finished before step D can begin.",
+            "Step E must be finished before step D can begin.",
+            "Step D must be finished before step F can begin.",
+            "Step F must be finished before step E can begin.",
+        ]);
+        let cycle = simulate(&graph, 2, &Durations::new(0)).unwrap_err();
+        assert_eq!(cycle.steps, vec!["D", "F", "E"]);
+    }
+}